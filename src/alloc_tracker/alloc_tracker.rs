@@ -0,0 +1,63 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static IN_REALTIME: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// wraps the system allocator and counts allocations that happen on a
+/// thread while it is inside a `Realtime` section. it is installed as the
+/// global allocator of the test build, so tests can check that the engine's
+/// audio path doesn't allocate.
+pub struct TrackingAllocator;
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        count();
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+fn count() {
+    // `try_with` because the thread locals may already be gone while a
+    // thread shuts down.
+    let in_realtime = IN_REALTIME.try_with(|r| r.get()).unwrap_or(false);
+    if in_realtime {
+        ALLOCATIONS.try_with(|a| a.set(a.get() + 1)).ok();
+    }
+}
+
+/// marks the current thread as being in the audio path until it is dropped.
+pub struct Realtime {
+    was_in_realtime: bool,
+}
+
+impl Realtime {
+    pub fn enter() -> Realtime {
+        Realtime { was_in_realtime: IN_REALTIME.with(|r| r.replace(true)) }
+    }
+}
+
+impl Drop for Realtime {
+    fn drop(&mut self) {
+        IN_REALTIME.with(|r| r.set(self.was_in_realtime));
+    }
+}
+
+/// how many allocations and deallocations happened inside `Realtime`
+/// sections on this thread so far. only counts when the `TrackingAllocator`
+/// is the global allocator.
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(|a| a.get())
+}
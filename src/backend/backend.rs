@@ -0,0 +1,94 @@
+extern crate portaudio;
+
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use engine::engine::Engine;
+use mesh::mesh::{MeshConfig, MeshError};
+use backend::file::FileBackend;
+use backend::null::NullBackend;
+use backend::pa_backend::PortAudioBackend;
+
+use self::portaudio as pa;
+
+#[derive(Debug)]
+pub enum BackendError {
+    PortAudio(pa::Error),
+    Io(io::Error),
+    // the mesh couldn't set up the engine.
+    Mesh(MeshError),
+    AlreadyRunning,
+    NotRunning,
+}
+
+impl From<pa::Error> for BackendError {
+    fn from(error: pa::Error) -> BackendError {
+        BackendError::PortAudio(error)
+    }
+}
+
+impl From<io::Error> for BackendError {
+    fn from(error: io::Error) -> BackendError {
+        BackendError::Io(error)
+    }
+}
+
+impl From<MeshError> for BackendError {
+    fn from(error: MeshError) -> BackendError {
+        BackendError::Mesh(error)
+    }
+}
+
+/// something that pulls audio out of an `Engine`. the backend owns the
+/// engine while it is running and decides when `Engine::process` is called.
+pub trait AudioBackend {
+    /// the engine was created with the same `config`.
+    fn start(&mut self, engine: Engine, config: &MeshConfig) -> Result<(), BackendError>;
+    /// gives the engine back, so that the next one can take over its graph.
+    fn stop(&mut self) -> Result<Engine, BackendError>;
+    fn name(&self) -> String;
+}
+
+/// creates a backend from a name, so it can be chosen at runtime.
+/// known names are "portaudio", "null" and "file:<path>".
+pub fn from_name(name: &str) -> Option<Box<AudioBackend>> {
+    match name {
+        "portaudio" => Option::Some(Box::new(PortAudioBackend::new())),
+        "null"      => Option::Some(Box::new(NullBackend::new())),
+        x if x.starts_with("file:") =>
+            Option::Some(Box::new(FileBackend::new(&x["file:".len()..]))),
+        _           => Option::None,
+    }
+}
+
+/// pulls one buffer of `config.frames_per_buffer` frames at a time out of
+/// the engine on a new thread and hands it to `sink`, pacing itself as if it
+/// was an audio device. the thread ends when `running` is set to false or
+/// when `sink` fails, and returns the engine.
+pub fn drive_on_timer<F>(mut engine: Engine, config: &MeshConfig, running: Arc<AtomicBool>,
+                         mut sink: F) -> thread::JoinHandle<(Engine, io::Result<()>)>
+    where F: FnMut(&[f32]) -> io::Result<()> + Send + 'static {
+
+    let period = Duration::from_nanos(
+        (config.frames_per_buffer as f64 / config.sample_rate * 1e9) as u64);
+    let samples = config.frames_per_buffer * config.channels;
+    thread::spawn(move || {
+        let mut buffer: Vec<f32> = vec![0.0; samples];
+        let mut deadline = Instant::now();
+        while running.load(Ordering::SeqCst) {
+            engine.process(&mut buffer);
+            match sink(&buffer) {
+                Ok(())   => (),
+                Err(err) => return (engine, Err(err)),
+            }
+            deadline += period;
+            let now = Instant::now();
+            if deadline > now {
+                thread::sleep(deadline - now);
+            }
+        }
+        (engine, Ok(()))
+    })
+}
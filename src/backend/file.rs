@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::PathBuf;
+use std::thread;
+use std::io;
+use engine::engine::Engine;
+use mesh::mesh::MeshConfig;
+use wav::wav::WavWriter;
+use backend::backend::{AudioBackend, BackendError, drive_on_timer};
+
+/// writes everything the engine produces to a wav file, in real time.
+/// the file is finished when the backend is stopped.
+pub struct FileBackend {
+    path: PathBuf,
+    running: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<(Engine, io::Result<()>)>>,
+}
+
+impl FileBackend {
+    pub fn new<P: Into<PathBuf>>(path: P) -> FileBackend {
+        FileBackend {
+            path: path.into(),
+            running: Arc::new(AtomicBool::new(false)),
+            thread: Option::None,
+        }
+    }
+}
+
+impl AudioBackend for FileBackend {
+    fn start(&mut self, engine: Engine, config: &MeshConfig) -> Result<(), BackendError> {
+        if self.thread.is_some() {
+            return Err(BackendError::AlreadyRunning);
+        }
+        let mut writer = try!(WavWriter::create(
+            &self.path, config.channels as u16, config.sample_rate as u32));
+        self.running.store(true, Ordering::SeqCst);
+        // the writer is dropped with the thread, which finishes the file.
+        self.thread = Option::Some(drive_on_timer(
+            engine, config, self.running.clone(), move |buffer| writer.write(buffer)));
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<Engine, BackendError> {
+        match self.thread.take() {
+            Some(thread) => {
+                self.running.store(false, Ordering::SeqCst);
+                let (engine, result) = thread.join().unwrap();
+                try!(result);
+                Ok(engine)
+            },
+            None => Err(BackendError::NotRunning),
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("file:{}", self.path.display())
+    }
+}
+
+impl Drop for FileBackend {
+    fn drop(&mut self) {
+        self.stop().ok();
+    }
+}
@@ -0,0 +1,4 @@
+pub mod backend;
+pub mod file;
+pub mod null;
+pub mod pa_backend;
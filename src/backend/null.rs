@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::io;
+use engine::engine::Engine;
+use mesh::mesh::MeshConfig;
+use backend::backend::{AudioBackend, BackendError, drive_on_timer};
+
+/// discards all audio. useful on machines without an audio device.
+pub struct NullBackend {
+    running: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<(Engine, io::Result<()>)>>,
+}
+
+impl NullBackend {
+    pub fn new() -> NullBackend {
+        NullBackend {
+            running: Arc::new(AtomicBool::new(false)),
+            thread: Option::None,
+        }
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn start(&mut self, engine: Engine, config: &MeshConfig) -> Result<(), BackendError> {
+        if self.thread.is_some() {
+            return Err(BackendError::AlreadyRunning);
+        }
+        self.running.store(true, Ordering::SeqCst);
+        self.thread = Option::Some(
+            drive_on_timer(engine, config, self.running.clone(), |_| Ok(())));
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<Engine, BackendError> {
+        match self.thread.take() {
+            Some(thread) => {
+                self.running.store(false, Ordering::SeqCst);
+                let (engine, result) = thread.join().unwrap();
+                try!(result);
+                Ok(engine)
+            },
+            None => Err(BackendError::NotRunning),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("null")
+    }
+}
+
+impl Drop for NullBackend {
+    fn drop(&mut self) {
+        self.stop().ok();
+    }
+}
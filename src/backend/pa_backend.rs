@@ -0,0 +1,81 @@
+extern crate portaudio;
+
+use std::sync::{Arc, Mutex};
+use engine::engine::Engine;
+use mesh::mesh::MeshConfig;
+use backend::backend::{AudioBackend, BackendError};
+
+use self::portaudio as pa;
+
+/// plays the engine's output on the default output device.
+pub struct PortAudioBackend {
+    stream: Option<pa::Stream<pa::NonBlocking, pa::Output<f32>>>,
+    // shared with the callback, which is the only one to lock it while the
+    // stream runs, so that `stop` can give it back.
+    engine: Option<Arc<Mutex<Engine>>>,
+}
+
+impl PortAudioBackend {
+    pub fn new() -> PortAudioBackend {
+        PortAudioBackend { stream: Option::None, engine: Option::None }
+    }
+}
+
+impl AudioBackend for PortAudioBackend {
+    fn start(&mut self, engine: Engine, config: &MeshConfig) -> Result<(), BackendError> {
+        if self.stream.is_some() {
+            return Err(BackendError::AlreadyRunning);
+        }
+        let pa = try!(pa::PortAudio::new());
+
+        let mut settings =
+            try!(pa.default_output_stream_settings(
+                    config.channels as i32, config.sample_rate,
+                    config.frames_per_buffer as u32));
+        // we won't output out of range samples so don't bother clipping them.
+        settings.flags = pa::stream_flags::CLIP_OFF;
+
+        let engine = Arc::new(Mutex::new(engine));
+        let shared = engine.clone();
+        // the buffer is interleaved, it holds `frames` samples per channel.
+        let callback = move |pa::OutputStreamCallbackArgs { buffer, .. }| {
+            // never waits, nothing else locks the engine while the stream runs.
+            match shared.try_lock() {
+                Ok(mut engine) => engine.process(buffer),
+                Err(_)         => for sample in buffer.iter_mut() {
+                    *sample = 0.0;
+                },
+            }
+            pa::Continue
+        };
+
+        let mut stream = try!(pa.open_non_blocking_stream(settings, callback));
+
+        try!(stream.start());
+        while !try!(stream.is_active()) { }
+        self.stream = Option::Some(stream);
+        self.engine = Option::Some(engine);
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<Engine, BackendError> {
+        match self.stream.take() {
+            Some(mut stream) => {
+                try!(stream.stop());
+                try!(stream.close());
+                // frees the callback and with it the other reference.
+                drop(stream);
+                let engine = self.engine.take().and_then(|engine| Arc::try_unwrap(engine).ok());
+                match engine.and_then(|engine| engine.into_inner().ok()) {
+                    Some(engine) => Ok(engine),
+                    None         => Err(BackendError::NotRunning),
+                }
+            },
+            None => Err(BackendError::NotRunning),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("portaudio")
+    }
+}
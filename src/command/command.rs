@@ -0,0 +1,260 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use mesh::mesh::MeshError;
+use registry::registry::RegistryError;
+use patch::patch::PatchError;
+
+/// a line of the prompt. processors are given by name or index, plugs as
+/// "processor.plug", see `Mesh::find_input`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    // (type, arguments, name), `<name> = new <type> [arguments]` gives the
+    // processor a name that later commands can use instead of its index.
+    New(String, Vec<String>, Option<String>),
+    Delete(String),
+    Name(String, String),
+    // (output, input)
+    Connect(String, String),
+    Feedback(String, String),
+    Disconnect(String, String),
+    // whether `connect` puts converters between plugs of different types.
+    AutoConvert(bool),
+    // (constant, value, ramp in seconds)
+    Constant(String, f64, Option<f64>),
+    List,
+    Ports(String),
+    Edges,
+    Order,
+    Save(String),
+    Load(String),
+    // runs a script file.
+    Run(String),
+    Help,
+    Quit,
+}
+
+#[derive(Debug)]
+pub enum CommandError {
+    UnclosedQuote,
+    UnknownCommand(String),
+    // the arguments don't fit, with how the command is used.
+    Usage(String),
+    InvalidNumber(String),
+    Mesh(MeshError),
+    Registry(RegistryError),
+    Patch(PatchError),
+    // a script that was run by `run`.
+    Script(Box<ScriptError>),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CommandError::UnclosedQuote =>
+                write!(f, "a quote is not closed"),
+            CommandError::UnknownCommand(ref name) =>
+                write!(f, "command \"{}\" not found, try `help`", name),
+            CommandError::Usage(ref usage) =>
+                write!(f, "usage: {}", usage),
+            CommandError::InvalidNumber(ref word) =>
+                write!(f, "\"{}\" is not a number", word),
+            CommandError::Mesh(ref err)     => write!(f, "{}", err),
+            CommandError::Registry(ref err) => write!(f, "{}", err),
+            CommandError::Patch(ref err)    => write!(f, "{}", err),
+            CommandError::Script(ref err)   => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for CommandError {}
+
+/// a script failed at `line`, counted from 1. nothing after it was run.
+#[derive(Debug)]
+pub enum ScriptError {
+    Io(io::Error),
+    Line(usize, CommandError),
+    // the script is already running, it would run itself forever.
+    Recursion(PathBuf),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScriptError::Io(ref err)         => write!(f, "{}", err),
+            ScriptError::Line(line, ref err) => write!(f, "line {}: {}", line, err),
+            ScriptError::Recursion(ref path) =>
+                write!(f, "{} is already running", path.display()),
+        }
+    }
+}
+
+impl error::Error for ScriptError {}
+
+impl From<io::Error> for ScriptError {
+    fn from(error: io::Error) -> ScriptError {
+        ScriptError::Io(error)
+    }
+}
+
+impl From<MeshError> for CommandError {
+    fn from(error: MeshError) -> CommandError {
+        CommandError::Mesh(error)
+    }
+}
+
+impl From<RegistryError> for CommandError {
+    fn from(error: RegistryError) -> CommandError {
+        CommandError::Registry(error)
+    }
+}
+
+impl From<PatchError> for CommandError {
+    fn from(error: PatchError) -> CommandError {
+        CommandError::Patch(error)
+    }
+}
+
+// (command, usage, what it does), in the order `help` shows them.
+const COMMANDS: &'static [(&'static str, &'static str, &'static str)] = &[
+    ("new",        "new <type> [arguments]",  "add a processor, e.g. `new dac 2`"),
+    ("new",        "<name> = new <type> ...", "add a processor called name"),
+    ("delete",     "delete <n>",              "remove processor n"),
+    ("name",       "name <n> <name>",         "rename processor n"),
+    ("connect",    "connect <out> <in>",      "connect two plugs, e.g. `connect sine.out dac.ch0`"),
+    ("connect",    "connect <a> <b> <c> <d>", "connect output b of a to input d of c"),
+    ("feedback",   "feedback <out> <in>",     "connect with a delay of one block, may close cycles"),
+    ("disconnect", "disconnect <out> <in>",   "remove a connection or feedback connection"),
+    ("convert",    "convert on|off",          "whether connect inserts converters between types"),
+    ("constant",   "constant <n> <value> [ramp]", "set constant n, ramping over ramp seconds"),
+    ("list",       "list",                    "show all processors"),
+    ("ports",      "ports <n>",               "show the inputs and outputs of processor n"),
+    ("edges",      "edges",                   "show all connections"),
+    ("order",      "order",                   "show the order processors are run in"),
+    ("save",       "save <path>",             "write the mesh to a patch file"),
+    ("load",       "load <path>",             "replace the mesh with a patch file"),
+    ("run",        "run <path>",              "run the commands in a file"),
+    ("help",       "help",                    "show this"),
+    ("quit",       "quit",                    "leave the prompt"),
+];
+
+/// every command with what it does, one per line.
+pub fn help() -> String {
+    COMMANDS.iter()
+        .map(|&(_, usage, description)| format!("{:<30}{}\n", usage, description))
+        .collect()
+}
+
+fn usage(name: &str) -> CommandError {
+    let usages: Vec<&str> = COMMANDS.iter()
+        .filter(|&&(command, _, _)| command == name)
+        .map(|&(_, usage, _)| usage)
+        .collect();
+    CommandError::Usage(usages.join(" or "))
+}
+
+/// splits a line into words at whitespace. single and double quotes group
+/// words with spaces, a backslash takes the next character as it is and
+/// `#` starts a comment that runs to the end of the line.
+pub fn split(line: &str) -> Result<Vec<String>, CommandError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // a word was started, it may still be empty, e.g. `""`.
+    let mut in_word = false;
+    let mut quote: Option<char> = Option::None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = Option::None,
+            (Some('\''), c) => word.push(c),
+            (_, '\\') => {
+                match chars.next() {
+                    Some(escaped) => word.push(escaped),
+                    None          => word.push('\\'),
+                }
+                in_word = true;
+            },
+            (Some(_), c) => word.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Option::Some(c);
+                in_word = true;
+            },
+            (None, '#') => break,
+            (None, c) if c.is_whitespace() => if in_word {
+                words.push(word);
+                word = String::new();
+                in_word = false;
+            },
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            },
+        }
+    }
+    if quote.is_some() {
+        return Err(CommandError::UnclosedQuote);
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// parses a line of the prompt. empty lines and comments give `None`.
+pub fn parse(line: &str) -> Result<Option<Command>, CommandError> {
+    let words = try!(split(line));
+    if words.is_empty() {
+        return Ok(Option::None);
+    }
+    if words.len() >= 2 && words[1] == "=" {
+        return match words.get(2).map(|word| word.as_str()) {
+            Some("new") if words.len() >= 4 =>
+                Ok(Option::Some(Command::New(words[3].clone(), words[4..].to_vec(),
+                                             Option::Some(words[0].clone())))),
+            Some("new") => Err(usage("new")),
+            _ => Err(CommandError::Usage(String::from("<name> = new <type> [arguments]"))),
+        };
+    }
+    let name = words[0].as_str();
+    let args = &words[1..];
+    let command = match (name, args.len()) {
+        ("new", n) if n >= 1  => Command::New(args[0].clone(), args[1..].to_vec(), Option::None),
+        ("delete", 1)         => Command::Delete(args[0].clone()),
+        ("name", 2)           => Command::Name(args[0].clone(), args[1].clone()),
+        ("connect", _)        => { let (o, i) = try!(plugs(name, args)); Command::Connect(o, i) },
+        ("feedback", _)       => { let (o, i) = try!(plugs(name, args)); Command::Feedback(o, i) },
+        ("disconnect", _)     => { let (o, i) = try!(plugs(name, args)); Command::Disconnect(o, i) },
+        ("convert", 1) if args[0] == "on"  => Command::AutoConvert(true),
+        ("convert", 1) if args[0] == "off" => Command::AutoConvert(false),
+        ("constant", 2)       => Command::Constant(args[0].clone(), try!(number(&args[1])),
+                                                   Option::None),
+        ("constant", 3)       => Command::Constant(args[0].clone(), try!(number(&args[1])),
+                                                   Option::Some(try!(number(&args[2])))),
+        ("list", 0)           => Command::List,
+        ("ports", 1)          => Command::Ports(args[0].clone()),
+        ("edges", 0)          => Command::Edges,
+        ("order", 0)          => Command::Order,
+        ("save", 1)           => Command::Save(args[0].clone()),
+        ("load", 1)           => Command::Load(args[0].clone()),
+        ("run", 1)            => Command::Run(args[0].clone()),
+        ("help", 0)           => Command::Help,
+        ("quit", 0)           => Command::Quit,
+        _ if COMMANDS.iter().any(|&(command, _, _)| command == name) => return Err(usage(name)),
+        _ => return Err(CommandError::UnknownCommand(name.to_string())),
+    };
+    Ok(Option::Some(command))
+}
+
+// "<out> <in>" or "<a> <b> <c> <d>", which is "a.b c.d".
+fn plugs(name: &str, args: &[String]) -> Result<(String, String), CommandError> {
+    match args.len() {
+        2 => Ok((args[0].clone(), args[1].clone())),
+        4 => Ok((format!("{}.{}", args[0], args[1]), format!("{}.{}", args[2], args[3]))),
+        _ => Err(usage(name)),
+    }
+}
+
+fn number(word: &str) -> Result<f64, CommandError> {
+    word.parse().map_err(|_| CommandError::InvalidNumber(word.to_string()))
+}
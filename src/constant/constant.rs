@@ -1,31 +1,137 @@
 use mesh::mesh::Processor;
-use mesh::mesh::Mesh;
+use mesh::mesh::Port;
 use mesh::mesh::Signal;
+use mesh::mesh::SAMPLERATE;
 
-pub struct Constant;
+/// how a `Constant` moves to a new value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Curve {
+    // the same step every frame.
+    Linear,
+    // the same ratio every frame, which sounds even for frequencies and
+    // gains. falls back to linear if either value is 0 or the sign changes.
+    Exponential,
+}
+
+/// outputs its value input. when the value changes it ramps to the new one
+/// over as many seconds as the ramp input says, so that changing a
+/// frequency or gain doesn't click.
+pub struct Constant {
+    curve: Curve,
+    sample_rate: f64,
+    // nothing was output yet, the first value is taken without a ramp.
+    started: bool,
+    current: f64,
+    target: f64,
+    // added to `current` every frame while ramping, or multiplied with it
+    // if `ratio` is set.
+    step: f64,
+    ratio: bool,
+    remaining: usize,
+}
 
 
 impl Processor for Constant {
     fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
-        vec![input[0].clone()]
+        vec![Signal::Sound(self.next(input[0], input[1]))]
+    }
+
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            output[0][frame] = Signal::Sound(self.next(input[0][frame], input[1][frame]));
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _block_size: usize) {
+        self.sample_rate = sample_rate;
     }
 
     fn input_types_and_defaults(&self) -> Vec<Signal> {
-        vec![Signal::Sound(0.0)]
+        vec![Signal::Sound(0.0), Signal::Sound(0.0)]
     }
 
     fn output_types(&self) -> Vec<Signal> {
         vec![Signal::Sound(0.0)]
     }
-    
+
+    fn input_ports(&self) -> Vec<Port> {
+        vec![Port::new("value", "the value, set with `Mesh::set_constant`"),
+             Port::new("ramp", "how long it takes to reach a new value").unit("s")]
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("out", "the value")]
+    }
+
     fn type_name(&self) -> String {
-        String::from("Constant")        
+        String::from("Constant")
+    }
+
+    fn arguments(&self) -> Option<Vec<String>> {
+        match self.curve {
+            Curve::Linear      => Option::Some(vec![]),
+            Curve::Exponential => Option::Some(vec![String::from("exponential")]),
+        }
     }
 }
 
 impl Constant {
 
     pub fn new() -> Constant {
-        Constant {}
+        Constant::with_curve(Curve::Linear)
+    }
+
+    pub fn with_curve(curve: Curve) -> Constant {
+        Constant {
+            curve: curve,
+            sample_rate: SAMPLERATE,
+            started: false,
+            current: 0.0,
+            target: 0.0,
+            step: 0.0,
+            ratio: false,
+            remaining: 0,
+        }
+    }
+
+    fn next(&mut self, target: Signal, ramp: Signal) -> f64 {
+        let target = target.value();
+        if !self.started || target != self.target {
+            let seconds = ramp.value();
+            let frames = if self.started { (seconds * self.sample_rate).round() } else { 0.0 };
+            self.start_ramp(target, if frames > 0.0 { frames as usize } else { 0 });
+        }
+        let value = self.current;
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.current = self.target;
+            } else {
+                if self.ratio {
+                    self.current *= self.step;
+                } else {
+                    self.current += self.step;
+                }
+            }
+        }
+        value
+    }
+
+    fn start_ramp(&mut self, target: f64, frames: usize) {
+        self.started = true;
+        self.target = target;
+        self.remaining = frames;
+        if frames == 0 {
+            self.current = target;
+            return;
+        }
+        let frames = frames as f64;
+        self.ratio = self.curve == Curve::Exponential && self.current * target > 0.0;
+        if self.ratio {
+            self.step = (target / self.current).powf(1.0 / frames);
+        } else {
+            self.step = (target - self.current) / frames;
+        }
     }
 }
@@ -0,0 +1,148 @@
+use mesh::mesh::Signal;
+use mesh::mesh::Processor;
+use mesh::mesh::Port;
+
+/// how `Quantize` turns a sound into an int.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rounding {
+    Round,
+    Floor,
+    Ceil,
+    Truncate,
+}
+
+impl Rounding {
+    pub fn from_name(name: &str) -> Option<Rounding> {
+        match name {
+            "round"    => Option::Some(Rounding::Round),
+            "floor"    => Option::Some(Rounding::Floor),
+            "ceil"     => Option::Some(Rounding::Ceil),
+            "truncate" => Option::Some(Rounding::Truncate),
+            _          => Option::None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Rounding::Round    => "round",
+            Rounding::Floor    => "floor",
+            Rounding::Ceil     => "ceil",
+            Rounding::Truncate => "truncate",
+        }
+    }
+}
+
+/// converts a sound into an int, e.g. to pick a step of a sequence.
+pub struct Quantize {
+    rounding: Rounding,
+}
+
+impl Processor for Quantize {
+    fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
+        vec![self.convert(input[0])]
+    }
+
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            output[0][frame] = self.convert(input[0][frame]);
+        }
+    }
+
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        vec![Signal::Sound(0.0)]
+    }
+
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Int(0)]
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        vec![Port::new("in", "the sound to convert")]
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("out", "the sound as an int")]
+    }
+
+    fn type_name(&self) -> String {
+        String::from("Quantize")
+    }
+
+    fn arguments(&self) -> Option<Vec<String>> {
+        Option::Some(vec![self.rounding.name().to_string()])
+    }
+}
+
+impl Quantize {
+    pub fn new() -> Quantize {
+        Quantize::with_rounding(Rounding::Round)
+    }
+
+    pub fn with_rounding(rounding: Rounding) -> Quantize {
+        Quantize { rounding: rounding }
+    }
+
+    fn convert(&self, input: Signal) -> Signal {
+        let value = input.value();
+        let rounded = match self.rounding {
+            Rounding::Round    => value.round(),
+            Rounding::Floor    => value.floor(),
+            Rounding::Ceil     => value.ceil(),
+            Rounding::Truncate => value.trunc(),
+        };
+        Signal::Int(rounded as i64)
+    }
+}
+
+/// converts an int into a sound.
+pub struct IntToSound;
+
+impl Processor for IntToSound {
+    fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
+        vec![Signal::Sound(input[0].value())]
+    }
+
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            output[0][frame] = Signal::Sound(input[0][frame].value());
+        }
+    }
+
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        vec![Signal::Int(0)]
+    }
+
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Sound(0.0)]
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        vec![Port::new("in", "the int to convert")]
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("out", "the int as a sound")]
+    }
+
+    fn type_name(&self) -> String {
+        String::from("IntToSound")
+    }
+}
+
+impl IntToSound {
+    pub fn new() -> IntToSound {
+        IntToSound {}
+    }
+}
+
+/// a processor that turns `output` signals into `input` signals, if there
+/// is one.
+pub fn converter(output: &Signal, input: &Signal) -> Option<Box<Processor>> {
+    match (*output, *input) {
+        (Signal::Sound(_), Signal::Int(_)) => Option::Some(Box::new(Quantize::new())),
+        (Signal::Int(_), Signal::Sound(_)) => Option::Some(Box::new(IntToSound::new())),
+        _                                  => Option::None,
+    }
+}
@@ -2,30 +2,52 @@ extern crate portaudio;
 
 use mesh::mesh::Signal;
 use mesh::mesh::Processor;
+use mesh::mesh::Port;
 use std::f64::consts::PI;
 
-pub struct Dac;
+/// sends its inputs to the audio output, input n to channel n.
+pub struct Dac {
+    channels: usize,
+}
 
 impl Processor for Dac {
     fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
         vec![]
     }
+    fn process_block(&mut self, _input: &[Vec<Signal>], _output: &mut [Vec<Signal>],
+                     _frames: usize) {
+    }
     fn input_types_and_defaults(&self) -> Vec<Signal> {
-        vec![Signal::Sound(0.0)]
+        vec![Signal::Sound(0.0); self.channels]
     }
     fn output_types(&self) -> Vec<Signal> {
         vec![]
     }
 
+    fn input_ports(&self) -> Vec<Port> {
+        (0..self.channels)
+            .map(|channel| Port::new(&format!("ch{}", channel),
+                                     &format!("output channel {}", channel)).range(-1.0, 1.0))
+            .collect()
+    }
+
     fn type_name(&self) -> String {
         String::from("Dac")
     }
 
+    fn arguments(&self) -> Option<Vec<String>> {
+        Option::Some(vec![self.channels.to_string()])
+    }
+
 }
 
 impl Dac {
     pub fn new() -> Dac {
-        Dac
+        Dac::with_channels(1)
+    }
+
+    pub fn with_channels(channels: usize) -> Dac {
+        Dac { channels: channels }
     }
 }
 
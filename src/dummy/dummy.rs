@@ -9,6 +9,10 @@ impl Processor for Dummy {
         vec![]
     }
 
+    fn process_block(&mut self, _input: &[Vec<Signal>], _output: &mut [Vec<Signal>],
+                     _frames: usize) {
+    }
+
     fn input_types_and_defaults(&self) -> Vec<Signal> {
         vec![]
     }
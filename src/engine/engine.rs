@@ -2,8 +2,7 @@
 
 extern crate portaudio;
 
-use std::sync::mpsc::{Sender, Receiver};
-use std::sync::mpsc;
+use std::mem;
 use std::thread;
 use std::option;
 use std::vec::Vec;
@@ -13,121 +12,254 @@ use add::add::Add;
 use mult::mult::Mult;
 use dac::dac::Dac;
 use sine::sine::Sine;
-use mesh::mesh::{Processor, Mesh, Signal};
-use dummy::dummy::Dummy;
+use mesh::mesh::{Processor, Mesh, Signal, MeshConfig};
+use ringbuffer::ringbuffer::{Consumer, Producer};
+#[cfg(debug_assertions)]
+use alloc_tracker::alloc_tracker::Realtime;
 
 use self::portaudio as pa;
 
-pub const SAMPLERATE: f64 = 44100.0;
-pub const CHANNELS: i32 = 1;
-pub const FRAMES_PER_BUFFER: u32 = 64;
-
 pub enum CallbackMessage {
-    Processor(Box<Processor>),
-    Connections(Vec<Vec<Vec<(usize, usize)>>>, Vec<usize>, Vec<usize>),
-    Constant(usize, f64),
-    ProcessorDeletion(usize),
+    Graph(Box<Graph>),
+    // (constant, value, ramp in seconds)
+    Constant(usize, f64, f64),
 }
 
-pub struct Engine {
-    pub processors: Vec<Box<Processor>>,
-    pub input_buffers: Vec<Vec<Signal>>, // computed signals are stored here until
-                                     //they get processed.
+/// what the engine tells the mesh about the messages it applied, see
+/// `Mesh::poll_events`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EngineEvent {
+    // a new graph is running, with this many processors.
+    GraphSwapped(usize),
+    ConstantSet(usize, f64),
+    // a value was sent to a processor that isn't a `Constant` in the
+    // running graph, it was ignored.
+    NotAConstant(usize),
+}
+
+/// everything the engine needs to run the mesh. a new graph is prepared on
+/// the control thread whenever the mesh changes and swapped in by the engine
+/// between blocks. the replaced graph is sent back so that it is freed on
+/// the control thread.
+pub struct Graph {
+    // `None` means the processor is taken over from the running graph,
+    // together with its buffers.
+    pub processors: Vec<Option<Box<Processor>>>,
+    //[processor][plug][frame], one block per plug
+    pub input_buffers: Vec<Vec<Vec<Signal>>>, // computed signals are stored here until
+                                              //they get processed.
+    pub output_buffers: Vec<Vec<Vec<Signal>>>,
     //[out_processor][out_plug][connection](in_processor, in_plug)
     pub adjacency_list: Vec<Vec<Vec<(usize, usize)>>>,
     pub topologically_ordered: Vec<usize>,
     pub io: Vec<usize>,
-    pub rec: Receiver<CallbackMessage>,
+    // ((out_processor, out_plug), (in_processor, in_plug)), copied after the
+    // whole block is processed, so the input sees it in the next block.
+    pub feedback: Vec<((usize, usize), (usize, usize))>,
+    pub is_constant: Vec<bool>,
+    // input plugs that lost their connection, with the defaults to reset
+    // them to once the graph is swapped in.
+    pub resets: Vec<(usize, usize, Signal)>,
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph {
+            processors: Vec::new(),
+            input_buffers: Vec::new(),
+            output_buffers: Vec::new(),
+            adjacency_list: Vec::new(),
+            topologically_ordered: Vec::new(),
+            io: Vec::new(),
+            feedback: Vec::new(),
+            is_constant: Vec::new(),
+            resets: Vec::new(),
+        }
+    }
+}
+
+pub struct Engine {
+    graph: Box<Graph>,
+    commands: Consumer<CallbackMessage>,
+    garbage: Producer<Box<Graph>>,
+    events: Producer<EngineEvent>,
+    channels: usize,
+    block_size: usize,
 }
 
 impl Engine {
 
-    fn add_processor(&mut self, processor: Box<Processor>) {
-        let typename = processor.type_name();
-        self.input_buffers.push(processor.input_types_and_defaults());
-        self.processors.push(processor);
-        println!("added processor: {}", typename);
+    /// `process` produces `config.channels` interleaved samples per frame.
+    /// replaced graphs are pushed to `garbage`, which needs to have room for
+    /// as many graphs as `commands` can hold. events that don't fit into
+    /// `events` are dropped.
+    pub fn new(commands: Consumer<CallbackMessage>, garbage: Producer<Box<Graph>>,
+               events: Producer<EngineEvent>, config: &MeshConfig) -> Engine {
+        Engine {
+            graph: Box::new(Graph::new()),
+            commands: commands,
+            garbage: garbage,
+            events: events,
+            channels: config.channels,
+            block_size: config.frames_per_buffer,
+        }
     }
 
-    fn remove_processor(&mut self, index: usize) {
-        self.processors[index] = Box::new(Dummy::new());
+    pub fn channels(&self) -> usize {
+        self.channels
     }
 
-    fn update_connections(&mut self, adjacency_list: Vec<Vec<Vec<(usize, usize)>>>, topologically_ordered: Vec<usize>, io: Vec<usize>) {
-        self.adjacency_list = adjacency_list;
-        self.topologically_ordered = topologically_ordered;
-        self.io = io;
+    /// starts from the graph an earlier engine of the same mesh was running,
+    /// see `into_graph`, so its processors go on where they were.
+    pub fn take_over(&mut self, graph: Box<Graph>) {
+        self.graph = graph;
     }
 
-    pub fn new(receiver: Receiver<CallbackMessage>) -> Engine {
-        Engine {
-            processors: Vec::new(),
-            input_buffers: Vec::new(),
-            adjacency_list: Vec::new(),
-            topologically_ordered: Vec::new(),
-            io: Vec::new(),
-            rec: receiver,
+    /// the graph the engine is running, after the messages that are still
+    /// waiting are applied.
+    pub fn into_graph(mut self) -> Box<Graph> {
+        self.apply_messages();
+        self.graph
+    }
+
+    fn swap_graph(&mut self, mut graph: Box<Graph>) {
+        for index in 0..graph.processors.len() {
+            if graph.processors[index].is_none() && index < self.graph.processors.len() {
+                mem::swap(&mut graph.processors[index], &mut self.graph.processors[index]);
+                mem::swap(&mut graph.input_buffers[index], &mut self.graph.input_buffers[index]);
+                mem::swap(&mut graph.output_buffers[index], &mut self.graph.output_buffers[index]);
+            }
         }
+        mem::swap(&mut self.graph, &mut graph);
+        self.reset_inputs();
+        let processors = self.graph.processors.len();
+        self.events.push(EngineEvent::GraphSwapped(processors)).ok();
+        // can't fail as long as `garbage` is as big as `commands`, see `new`.
+        self.garbage.push(graph).ok();
     }
 
-    pub fn set_constant(&mut self, constant: usize, value: f64) {
-        println!("setting constant now");
-        if self.processors[constant].type_name() == "Constant" {
-            self.input_buffers[constant][0] = Signal::Sound(value);
-        } else {
-            println!("this processor is no constant");
+    fn reset_inputs(&mut self) {
+        let graph = &mut *self.graph;
+        for &(processor, plug, default) in &graph.resets {
+            match graph.input_buffers.get_mut(processor).and_then(|p| p.get_mut(plug)) {
+                Some(buffer) => for signal in buffer.iter_mut() {
+                    *signal = default;
+                },
+                None => (),
+            }
         }
-        println!("constant set");
     }
 
-    pub fn process(&mut self) -> Vec<f32> {
-        for i in 0..3 {
-            match self.rec.try_recv() {
-            Result::Ok(a) => match a {
-                CallbackMessage::Processor(a) => self.add_processor(a),
-                CallbackMessage::Connections(adj, topo, io) => self.update_connections(adj, topo, io),
-                CallbackMessage::Constant(a, b) => self.set_constant(a, b),
-                CallbackMessage::ProcessorDeletion(a) => self.remove_processor(a),
-            },
-	        Result::Err(_) => (),
+    /// the constant ramps to `value` itself, see `Constant`.
+    pub fn set_constant(&mut self, constant: usize, value: f64, ramp: f64) {
+        if self.graph.is_constant.get(constant) != Option::Some(&true) {
+            self.events.push(EngineEvent::NotAConstant(constant)).ok();
+            return;
         }
+        let inputs = &mut self.graph.input_buffers[constant];
+        for (buffer, &value) in inputs.iter_mut().zip([value, ramp].iter()) {
+            for signal in buffer.iter_mut() {
+                *signal = Signal::Sound(value);
+            }
         }
-        {
-            let mut processors = &mut self.processors;
-            for processor_num in &self.topologically_ordered {
-                let processor_num_two = *processor_num;
-                let processor_num_three = *processor_num;
-                let mut boxed_processor: &mut Box<Processor> = &mut processors[*processor_num];
-                let mut processor = boxed_processor;
-                let result: Vec<Signal>;
-                {
-                    let input : &Vec<Signal> = &self.input_buffers[processor_num_two];
-                    result = processor.process(input);
-                }
-                let connections: &Vec<Vec<(usize, usize)>> = 
-                    &self.adjacency_list[processor_num_three];
-                for (plug_num, plug) in connections.iter().enumerate() {
-                    for other in plug {
-                        let (other_processor_num, other_input) = *other;
-                        self.input_buffers[other_processor_num][other_input] = result[plug_num].clone();
+        self.events.push(EngineEvent::ConstantSet(constant, value)).ok();
+    }
+
+    /// applies every message that is waiting, in the order they were sent.
+    pub fn apply_messages(&mut self) {
+        while let Option::Some(message) = self.commands.pop() {
+            match message {
+                CallbackMessage::Graph(graph) => self.swap_graph(graph),
+                CallbackMessage::Constant(a, b, c) => self.set_constant(a, b, c),
+            }
+        }
+    }
+
+    /// renders `frames` frames synchronously without an audio device.
+    /// pending messages are applied before the first frame, so the result
+    /// only depends on what was sent before the call.
+    pub fn render(&mut self, frames: usize) -> Vec<f32> {
+        let mut samples = vec![0.0; frames * self.channels];
+        self.process(&mut samples);
+        samples
+    }
+
+    /// fills `output` with interleaved frames, `channels` samples each.
+    /// the graph is run one block of at most `frames_per_buffer` frames at
+    /// a time, messages are applied between blocks. nothing in here
+    /// allocates, frees or locks, as long as the processors don't.
+    pub fn process(&mut self, output: &mut [f32]) {
+        #[cfg(debug_assertions)]
+        let _realtime = Realtime::enter();
+        if self.channels == 0 {
+            return;
+        }
+        let block_samples = self.block_size * self.channels;
+        for block in output.chunks_mut(block_samples) {
+            self.apply_messages();
+            let frames = block.len() / self.channels;
+            self.process_block(frames);
+            self.write_output(block, frames);
+        }
+    }
+
+    fn process_block(&mut self, frames: usize) {
+        let graph = &mut *self.graph;
+        for processor_num in &graph.topologically_ordered {
+            let processor_num = *processor_num;
+            match graph.processors[processor_num] {
+                Some(ref mut processor) => processor.process_block(
+                    &graph.input_buffers[processor_num],
+                    &mut graph.output_buffers[processor_num],
+                    frames),
+                None => continue,
+            }
+            let connections: &Vec<Vec<(usize, usize)>> =
+                &graph.adjacency_list[processor_num];
+            for (plug_num, plug) in connections.iter().enumerate() {
+                let result = &graph.output_buffers[processor_num][plug_num][..frames];
+                for other in plug {
+                    let (other_processor_num, other_input) = *other;
+                    match graph.input_buffers.get_mut(other_processor_num)
+                        .and_then(|p| p.get_mut(other_input)) {
+                        Some(buffer) => buffer[..frames].copy_from_slice(result),
+                        None         => (),
                     }
                 }
             }
         }
-        for io_processor_num in &self.io {
-            let io_processor = &self.processors[*io_processor_num];
-            if io_processor.type_name() == "Dac" {
-                //TODO: implement properly, only supports one channel
-                let signal: f32;
-                match self.input_buffers[*io_processor_num][0] {
-                    Signal::Sound(a) => signal = a as f32,
-                    _                => panic!(),
+        for &((out_processor, out_plug), (in_processor, in_plug)) in &graph.feedback {
+            let result = match graph.output_buffers.get(out_processor)
+                .and_then(|p| p.get(out_plug)) {
+                Some(result) => &result[..frames],
+                None         => continue,
+            };
+            match graph.input_buffers.get_mut(in_processor).and_then(|p| p.get_mut(in_plug)) {
+                Some(buffer) => buffer[..frames].copy_from_slice(result),
+                None         => (),
+            }
+        }
+    }
+
+    // input plug n of every Dac is summed into channel n.
+    fn write_output(&self, output: &mut [f32], frames: usize) {
+        for sample in output.iter_mut() {
+            *sample = 0.0;
+        }
+        for io_processor_num in &self.graph.io {
+            let inputs = match self.graph.input_buffers.get(*io_processor_num) {
+                Some(inputs) => inputs,
+                None         => continue,
+            };
+            for (channel, input) in inputs.iter().take(self.channels).enumerate() {
+                for frame in 0..frames {
+                    match input[frame] {
+                        Signal::Sound(a) => output[frame * self.channels + channel] += a as f32,
+                        _                => panic!(),
+                    }
                 }
-                return vec![signal];
             }
         }
-        return vec![0.0];
     }
 
 }
-
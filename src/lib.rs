@@ -1,3 +1,9 @@
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+extern crate rustyline;
+
 pub mod mesh;
 pub mod engine;
 pub mod tests;
@@ -7,5 +13,21 @@ pub mod add;
 pub mod mult;
 pub mod dummy;
 pub mod constant;
+pub mod backend;
+pub mod wav;
+pub mod ringbuffer;
+pub mod alloc_tracker;
+pub mod patch;
+pub mod registry;
+pub mod command;
+pub mod convert;
+pub mod oscillator;
+pub mod wavetable;
+pub mod noise;
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: alloc_tracker::alloc_tracker::TrackingAllocator =
+    alloc_tracker::alloc_tracker::TrackingAllocator;
 
 
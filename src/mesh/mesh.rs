@@ -1,28 +1,34 @@
 #![allow(dead_code)]
 
-extern crate portaudio;
-
-use std::io::{self, Read};
-use std::sync::mpsc::{Sender, Receiver};
-use std::sync::mpsc;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::thread;
+use std::time::Duration;
 use std::option;
 use std::vec::Vec;
 use std::collections::LinkedList;
 use std::collections::HashMap;
-use add::add::Add;
-use mult::mult::Mult;
-use dac::dac::Dac;
-use sine::sine::Sine;
-use engine::engine::{Engine, CallbackMessage};
+use engine::engine::{Engine, EngineEvent, CallbackMessage, Graph};
+use ringbuffer::ringbuffer::{ring_buffer, Producer, Consumer};
 use dummy::dummy::Dummy;
-use constant::constant::Constant;
-
-use self::portaudio as pa;
+use backend::backend::{AudioBackend, BackendError};
+use backend::pa_backend::PortAudioBackend;
+use patch::patch::{Patch, PatchProcessor, PatchConnection, PatchError};
+use registry::registry::{ProcessorRegistry, RegistryError};
+use convert::convert;
+use command::command::{self, Command, CommandError, ScriptError};
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
 
+// defaults for `MeshConfig`.
 pub const SAMPLERATE: f64 = 44100.0;
 pub const CHANNELS: i32 = 1;
 pub const FRAMES_PER_BUFFER: u32 = 64;
+// how many messages can wait for the engine before `Mesh` has to block.
+const QUEUE_SIZE: usize = 1024;
 
 type AdjList = Vec<Vec<Vec<(usize, usize)>>>;
 
@@ -67,52 +73,342 @@ fn io_clone(input: &IoList) -> IoList {
     out
 }
 
-pub trait Processor {
+pub trait Processor: Send {
     fn process(self: &mut Self, input: &Vec<Signal>) -> Vec<Signal>;
+
+    /// called on the control thread before the processor is handed to the
+    /// engine, with the sample rate it will run at and the maximum number
+    /// of frames `process_block` will be asked for.
+    fn prepare(self: &mut Self, _sample_rate: f64, _block_size: usize) {
+    }
+
+    /// processes a whole block at once. `input[plug]` and `output[plug]` are
+    /// preallocated and at least `frames` long, only the first `frames`
+    /// signals are used. the default calls `process` for every frame, which
+    /// allocates, so processors should implement this themselves.
+    fn process_block(self: &mut Self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        let mut frame_input: Vec<Signal> = Vec::with_capacity(input.len());
+        for frame in 0..frames {
+            frame_input.clear();
+            for plug in input {
+                frame_input.push(plug[frame]);
+            }
+            for (plug, signal) in self.process(&frame_input).into_iter().enumerate() {
+                output[plug][frame] = signal;
+            }
+        }
+    }
+
     fn input_types_and_defaults(self: &Self) -> Vec<Signal>;
     fn output_types(self: &Self) -> Vec<Signal>;
     fn type_name(self: &Self) -> String;
+
+    /// the arguments the `ProcessorRegistry` needs to build this processor
+    /// again. they are written to patch files. `None` if it can't be built
+    /// from arguments, then it can't be saved either.
+    fn arguments(self: &Self) -> Option<Vec<String>> {
+        Option::Some(Vec::new())
+    }
+
+    /// describes the input plugs, in the order of `input_types_and_defaults`.
+    fn input_ports(self: &Self) -> Vec<Port> {
+        numbered_ports("in", self.input_types_and_defaults().len())
+    }
+
+    /// describes the output plugs, in the order of `output_types`.
+    fn output_ports(self: &Self) -> Vec<Port> {
+        numbered_ports("out", self.output_types().len())
+    }
+}
+
+/// what an input or output plug is for. plugs can be addressed by name,
+/// e.g. "sine.freq", see `Mesh::find_input`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Port {
+    pub name: String,
+    pub description: String,
+    // e.g. "Hz", empty if the signal has no unit.
+    pub unit: String,
+    // the values that make sense, if there is such a range.
+    pub range: Option<(f64, f64)>,
+}
+
+impl Port {
+    pub fn new(name: &str, description: &str) -> Port {
+        Port {
+            name: name.to_string(),
+            description: description.to_string(),
+            unit: String::new(),
+            range: Option::None,
+        }
+    }
+
+    pub fn unit(mut self, unit: &str) -> Port {
+        self.unit = unit.to_string();
+        self
+    }
+
+    pub fn range(mut self, min: f64, max: f64) -> Port {
+        self.range = Option::Some((min, max));
+        self
+    }
+}
+
+/// `count` ports called `<prefix>0`, `<prefix>1`, ... without a description.
+pub fn numbered_ports(prefix: &str, count: usize) -> Vec<Port> {
+    (0..count).map(|plug| Port::new(&format!("{}{}", prefix, plug), "")).collect()
+}
+
+/// what went wrong when editing a mesh. plugs are given as
+/// (processor, plug), connections as (output, input).
+#[derive(Clone, Debug, PartialEq)]
+pub enum MeshError {
+    NoSuchProcessor(usize),
+    NoSuchOutput(usize, usize),
+    NoSuchInput(usize, usize),
+    TypeMismatch((usize, usize), (usize, usize)),
+    Cycle((usize, usize), (usize, usize)),
+    NoSuchConnection((usize, usize), (usize, usize)),
+    // there is no engine yet, see `Mesh::run` and `Mesh::run_offline`.
+    NotRunning,
+    // the mesh is playing on an audio backend, see `Mesh::stop`.
+    AlreadyRunning,
+    // the graph of the last engine was lost, so a new engine needs every
+    // processor built again, and the registry couldn't build this one.
+    Rebuild(usize, RegistryError),
+    // the processor has no arguments it could be built again from, see
+    // `Processor::arguments`.
+    NoArguments(usize),
+    // values can only be set on a `Constant`.
+    NotAConstant(usize),
+    // a processor or plug name like "sine.freq" that doesn't resolve.
+    UnknownName(String),
+    // a processor name that is taken or can't be told apart from a plug.
+    InvalidName(String),
+    // says what is wrong with the `MeshConfig`.
+    InvalidConfig(String),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MeshError::NoSuchProcessor(p) =>
+                write!(f, "there is no processor {}", p),
+            MeshError::NoSuchOutput(p, plug) =>
+                write!(f, "processor {} has no output {}", p, plug),
+            MeshError::NoSuchInput(p, plug) =>
+                write!(f, "processor {} has no input {}", p, plug),
+            MeshError::TypeMismatch(o, i) =>
+                write!(f, "output {}.{} and input {}.{} have different types", o.0, o.1, i.0, i.1),
+            MeshError::Cycle(o, i) =>
+                write!(f, "connecting {}.{} to {}.{} would create a cycle, use a feedback connection",
+                       o.0, o.1, i.0, i.1),
+            MeshError::NoSuchConnection(o, i) =>
+                write!(f, "there is no connection from {}.{} to {}.{}", o.0, o.1, i.0, i.1),
+            MeshError::NotRunning =>
+                write!(f, "the mesh is not running"),
+            MeshError::AlreadyRunning =>
+                write!(f, "the mesh is already running"),
+            MeshError::Rebuild(p, ref err) =>
+                write!(f, "processor {} can't be built for the new engine: {}", p, err),
+            MeshError::NoArguments(p) =>
+                write!(f, "processor {} can't be built again from arguments", p),
+            MeshError::NotAConstant(p) =>
+                write!(f, "processor {} is no constant", p),
+            MeshError::UnknownName(ref name) =>
+                write!(f, "there is no processor or plug \"{}\"", name),
+            MeshError::InvalidName(ref name) =>
+                write!(f, "\"{}\" can't be used as a processor name", name),
+            MeshError::InvalidConfig(ref problem) =>
+                write!(f, "invalid configuration: {}", problem),
+        }
+    }
+}
+
+impl error::Error for MeshError {}
+
+/// how a mesh runs. backends and the offline engine are set up from this.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshConfig {
+    pub sample_rate: f64,
+    // interleaved samples per frame.
+    pub channels: usize,
+    pub frames_per_buffer: usize,
+}
+
+impl MeshConfig {
+    pub fn new() -> MeshConfig {
+        MeshConfig {
+            sample_rate: SAMPLERATE,
+            channels: CHANNELS as usize,
+            frames_per_buffer: FRAMES_PER_BUFFER as usize,
+        }
+    }
+
+    /// the engine can't run with an empty buffer or without channels, and
+    /// every processor needs a positive sample rate.
+    pub fn check(&self) -> Result<(), MeshError> {
+        if !(self.sample_rate.is_finite() && self.sample_rate > 0.0) {
+            return Err(MeshError::InvalidConfig(
+                format!("the sample rate is {}, it has to be positive", self.sample_rate)));
+        }
+        if self.frames_per_buffer == 0 {
+            return Err(MeshError::InvalidConfig(String::from("there are no frames per buffer")));
+        }
+        if self.channels == 0 {
+            return Err(MeshError::InvalidConfig(String::from("there are no channels")));
+        }
+        Ok(())
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Signal {
     Sound(f64),
     Int(i64),
+    // true on the one frame an event happens, e.g. to restart an envelope.
+    Trigger(bool),
+    // true for as long as something is held, e.g. a key.
+    Gate(bool),
+}
+
+impl Signal {
+    /// "sound", "int", "trigger" or "gate".
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Signal::Sound(_)   => "sound",
+            Signal::Int(_)     => "int",
+            Signal::Trigger(_) => "trigger",
+            Signal::Gate(_)    => "gate",
+        }
+    }
+
+    /// whether `other` is the same variant, only those can be connected.
+    pub fn same_type(&self, other: &Signal) -> bool {
+        self.type_name() == other.type_name()
+    }
+
+    /// the signal as a number, triggers and gates are 1 when set.
+    pub fn value(&self) -> f64 {
+        match *self {
+            Signal::Sound(a)   => a,
+            Signal::Int(a)     => a as f64,
+            Signal::Trigger(a) => if a { 1.0 } else { 0.0 },
+            Signal::Gate(a)    => if a { 1.0 } else { 0.0 },
+        }
+    }
+
+    /// a signal of the same type as this one that holds `value`, the
+    /// opposite of `value`. ints are rounded towards zero.
+    pub fn with_value(&self, value: f64) -> Signal {
+        match *self {
+            Signal::Sound(_)   => Signal::Sound(value),
+            Signal::Int(_)     => Signal::Int(value as i64),
+            Signal::Trigger(_) => Signal::Trigger(value != 0.0),
+            Signal::Gate(_)    => Signal::Gate(value != 0.0),
+        }
+    }
 }
 
-impl Clone for Signal {
-    fn clone(&self) -> Signal {
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Signal::Sound(a) => return Signal::Sound(a),
-            Signal::Int(a)   => return Signal::Int(a),
+            Signal::Sound(a)   => write!(f, "sound {}", a),
+            Signal::Int(a)     => write!(f, "int {}", a),
+            Signal::Trigger(a) => write!(f, "trigger {}", a),
+            Signal::Gate(a)    => write!(f, "gate {}", a),
         }
     }
 }
 
 
+
 pub struct Mesh {
     pub processor_types: Vec<(Vec<Signal>, Vec<Signal>, String)>,
+    arguments: Vec<Option<Vec<String>>>,
+    // (inputs, outputs) of every processor.
+    ports: Vec<(Vec<Port>, Vec<Port>)>,
+    // unique, empty for deleted processors.
+    names: Vec<String>,
     pub input_buffers: Vec<Vec<Signal>>, // computed signals are stored here until
                                          //they get processed.
     //[out_processor][out_plug][connection](in_processor, in_plug)
     adjacency_list: AdjList,
     pub topologically_ordered: TopoList,
     ios: IoList,
-    tx: Option<Sender<CallbackMessage>>,
+    // (output, input) pairs that are delayed by one block, see `connect_feedback`.
+    feedback: Vec<((usize, usize), (usize, usize))>,
+    tx: Option<Producer<CallbackMessage>>,
+    garbage: Option<Consumer<Box<Graph>>>,
+    events: Option<Consumer<EngineEvent>>,
+    // processors that were added while there was no engine.
+    unsent: Vec<(usize, Box<Processor>)>,
+    offline: Option<Engine>,
+    backend: Option<Box<AudioBackend>>,
+    // what the last engine was running when it stopped, the next one goes
+    // on with it.
+    stopped_graph: Option<Box<Graph>>,
+    config: MeshConfig,
+    registry: ProcessorRegistry,
+    // put converters between plugs of different types, see `set_auto_convert`.
+    auto_convert: bool,
+    // the script files that are running, the outermost first.
+    scripts: Vec<PathBuf>,
 }
 
 impl Mesh {
 
     pub fn new() -> Mesh {
+        Mesh::checked(MeshConfig::new())
+    }
+
+    pub fn with_config(config: MeshConfig) -> Result<Mesh, MeshError> {
+        try!(config.check());
+        Ok(Mesh::checked(config))
+    }
+
+    // `config` has to be valid, see `MeshConfig::check`.
+    fn checked(config: MeshConfig) -> Mesh {
         Mesh {
             processor_types: Vec::new(),
+            arguments: Vec::new(),
+            ports: Vec::new(),
+            names: Vec::new(),
             input_buffers: Vec::new(),
             adjacency_list: Vec::new(),
             tx: Option::None,
+            garbage: Option::None,
+            events: Option::None,
+            unsent: Vec::new(),
             topologically_ordered: Option::Some(Vec::new()),
             ios: Vec::new(),
+            feedback: Vec::new(),
+            offline: Option::None,
+            backend: Option::None,
+            stopped_graph: Option::None,
+            config: config,
+            registry: ProcessorRegistry::new(),
+            auto_convert: false,
+            scripts: Vec::new(),
         }
     }
 
+    pub fn config(&self) -> &MeshConfig {
+        &self.config
+    }
+
+    /// the processors that can be created by name, from the prompt and
+    /// from patch files.
+    pub fn registry(&self) -> &ProcessorRegistry {
+        &self.registry
+    }
+
+    /// add your own processors here to use them in the prompt and in patches.
+    pub fn registry_mut(&mut self) -> &mut ProcessorRegistry {
+        &mut self.registry
+    }
+
     pub fn register_processor(self: &mut Mesh, processor: Box<Processor>) -> Box<Processor> {
 
         self.adjacency_list.push(Vec::new());
@@ -135,154 +431,792 @@ impl Mesh {
         self.processor_types.push(((*processor).input_types_and_defaults(),
                                    (*processor).output_types(),
                                    (*processor).type_name()));
+        self.arguments.push(processor.arguments());
+        self.ports.push((processor.input_ports(), processor.output_ports()));
+        let name = self.free_name(&processor.type_name().to_lowercase());
+        self.names.push(name);
         self.order_topologically();
         processor
     }
 
-    pub fn run(&mut self) -> Result<pa::Stream<pa::NonBlocking, pa::Output<f32>>, pa::Error> {
-        let pa = try!(pa::PortAudio::new());
-        let (tx, rx): (mpsc::Sender<CallbackMessage>,
-                       mpsc::Receiver<CallbackMessage>) = mpsc::channel();
-        let mut engine: Engine =  Engine::new(rx);
+    /// plays the mesh on the default PortAudio output device.
+    pub fn run(&mut self) -> Result<(), BackendError> {
+        self.run_with(Box::new(PortAudioBackend::new()))
+    }
 
-        let mut settings = 
-            try!(pa.default_output_stream_settings(
-                    CHANNELS, SAMPLERATE, FRAMES_PER_BUFFER));
-        // we won't output out of range samples so don't bother clipping them.
-        settings.flags = pa::stream_flags::CLIP_OFF;
-        
+    /// hands a fresh engine to `backend` and keeps the backend running
+    /// until `stop` is called or the mesh is dropped.
+    pub fn run_with(&mut self, mut backend: Box<AudioBackend>) -> Result<(), BackendError> {
+        if self.backend.is_some() {
+            return Err(BackendError::AlreadyRunning);
+        }
+        let (engine, rebuilt) = try!(self.next_engine());
+        // handed over first, so the first block already has the graph. if
+        // the backend fails to start, the processors go with the engine
+        // and are built again next time.
+        self.hand_over(rebuilt);
+        match backend.start(engine, &self.config) {
+            Ok(())   => (),
+            Err(err) => {
+                self.tx = Option::None;
+                self.garbage = Option::None;
+                self.events = Option::None;
+                return Err(err);
+            },
+        }
+        self.backend = Option::Some(backend);
+        Ok(())
+    }
 
-        let callback = move |pa::OutputStreamCallbackArgs { buffer, frames, .. }| {
-            let mut idx = 0;
-            for _ in 0..frames {
-                buffer[idx] = engine.process()[0];
-                idx += 1;
-            }
-            pa::Continue
+    /// the processors are kept as they are, so `run` and `run_offline` go
+    /// on where they stopped.
+    pub fn stop(&mut self) -> Result<(), BackendError> {
+        match self.backend.take() {
+            Some(mut backend) => {
+                self.tx = Option::None;
+                self.garbage = Option::None;
+                self.events = Option::None;
+                let engine = try!(backend.stop());
+                self.stopped_graph = Option::Some(engine.into_graph());
+                Ok(())
+            },
+            None => Err(BackendError::NotRunning),
+        }
+    }
+
+    /// creates an engine that is not attached to an audio device. it only
+    /// produces samples when `render` is called. an offline engine that
+    /// was created before is replaced, the new one goes on with its
+    /// processors.
+    pub fn run_offline(&mut self) -> Result<(), MeshError> {
+        if self.backend.is_some() {
+            return Err(MeshError::AlreadyRunning);
+        }
+        let (engine, rebuilt) = try!(self.next_engine());
+        self.offline = Option::Some(engine);
+        self.hand_over(rebuilt);
+        Ok(())
+    }
+
+    /// a new engine that goes on with the graph of the last one. if that is
+    /// lost, e.g. because a backend failed to start, the processors are
+    /// built again, which have to be handed over with `hand_over`.
+    fn next_engine(&mut self) -> Result<(Engine, Vec<(usize, Box<Processor>)>), MeshError> {
+        let previous = match self.offline.take() {
+            Some(engine) => Option::Some(engine.into_graph()),
+            None         => self.stopped_graph.take(),
+        };
+        let rebuilt = match previous {
+            Some(_) => Vec::new(),
+            None    => try!(self.rebuild()),
         };
+        let mut engine = self.new_engine();
+        match previous {
+            Some(graph) => engine.take_over(graph),
+            None        => (),
+        }
+        Ok((engine, rebuilt))
+    }
+
+    /// builds the processors an earlier engine took with it again, with the
+    /// registry. processors that no engine has seen yet are still in
+    /// `unsent`, those aren't built.
+    fn rebuild(&self) -> Result<Vec<(usize, Box<Processor>)>, MeshError> {
+        let mut rebuilt = Vec::new();
+        for (index, types) in self.processor_types.iter().enumerate() {
+            if types.2 == "Dummy" || self.unsent.iter().any(|&(unsent, _)| unsent == index) {
+                continue;
+            }
+            let arguments: Vec<&str> = match self.arguments[index] {
+                Some(ref arguments) => arguments.iter().map(|a| a.as_str()).collect(),
+                None                => return Err(MeshError::NoArguments(index)),
+            };
+            let mut processor = try!(self.registry.create(&types.2, &arguments)
+                .map_err(|err| MeshError::Rebuild(index, err)));
+            processor.prepare(self.config.sample_rate, self.config.frames_per_buffer);
+            rebuilt.push((index, processor));
+        }
+        Ok(rebuilt)
+    }
 
-        let mut stream = try!(pa.open_non_blocking_stream(settings, callback));
+    /// gives a fresh engine everything that was set up before it existed,
+    /// with the processors from `rebuild`.
+    fn hand_over(&mut self, rebuilt: Vec<(usize, Box<Processor>)>) {
+        // processors taken over from the last engine get the values their
+        // inputs were set to since.
+        let mut resets = Vec::new();
+        for (processor, inputs) in self.input_buffers.iter().enumerate() {
+            for (plug, &value) in inputs.iter().enumerate() {
+                if !self.is_connected((processor, plug)) {
+                    resets.push((processor, plug, value));
+                }
+            }
+        }
+        // can't fail, there is an engine now.
+        self.send_graph(rebuilt, resets).ok();
+    }
 
-        try!(stream.start());
-        while !stream.is_active().unwrap() { }
-        //try!(stream.stop());
-        //try!(stream.close());
+    fn new_engine(&mut self) -> Engine {
+        let (tx, commands) = ring_buffer(QUEUE_SIZE);
+        let (garbage, rx) = ring_buffer(QUEUE_SIZE);
+        let (events, received) = ring_buffer(QUEUE_SIZE);
         self.tx = Option::Some(tx);
-        Ok(stream)
+        self.garbage = Option::Some(rx);
+        self.events = Option::Some(received);
+        Engine::new(commands, garbage, events, &self.config)
     }
 
+    /// what the engine did since the last call, oldest first. the engine
+    /// drops events while `QUEUE_SIZE` of them are waiting, so call this
+    /// regularly if you care about them.
+    pub fn poll_events(&mut self) -> Vec<EngineEvent> {
+        let mut events = Vec::new();
+        match self.events {
+            Some(ref mut received) => while let Some(event) = received.pop() {
+                events.push(event);
+            },
+            None => (),
+        }
+        events
+    }
+
+    /// frees the graphs the engine has replaced.
+    fn collect_garbage(&mut self) {
+        match self.garbage {
+            Some(ref mut garbage) => while let Some(_) = garbage.pop() {},
+            None                  => (),
+        }
+    }
+
+    /// blocks while the engine's queue is full.
+    fn send(&mut self, message: CallbackMessage) -> Result<(), MeshError> {
+        let mut message = message;
+        loop {
+            self.collect_garbage();
+            match self.tx {
+                Some(ref mut tx) => match tx.push(message) {
+                    Ok(())        => return Ok(()),
+                    Err(returned) => message = returned,
+                },
+                None => return Err(MeshError::NotRunning),
+            }
+            match self.offline {
+                Some(ref mut engine) => engine.apply_messages(),
+                None                 => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+    }
+
+    /// sends the current graph to the engine. `new` are processors the
+    /// engine doesn't have yet, all others are taken over from the graph it
+    /// is running. without an engine, the new processors are kept so the
+    /// whole graph can be handed over once there is one.
+    fn send_graph(&mut self, new: Vec<(usize, Box<Processor>)>,
+                  resets: Vec<(usize, usize, Signal)>) -> Result<(), MeshError> {
+        for &(processor, plug, default) in &resets {
+            self.input_buffers[processor][plug] = default;
+        }
+        if self.tx.is_none() {
+            // kept until there is an engine, see `hand_over`.
+            self.unsent.extend(new);
+            return Ok(());
+        }
+        let mut new = new;
+        new.extend(self.unsent.drain(..));
+        let block_size = self.config.frames_per_buffer;
+        let mut graph = Box::new(Graph::new());
+        for types in &self.processor_types {
+            graph.processors.push(Option::None);
+            graph.input_buffers.push(Vec::new());
+            graph.output_buffers.push(Vec::new());
+            graph.is_constant.push(types.2 == "Constant");
+        }
+        // new processors start with the values the mesh has for their
+        // inputs, which includes constants set before the engine existed.
+        for (index, processor) in new {
+            graph.input_buffers[index] = self.input_buffers[index].iter()
+                .map(|value| vec![*value; block_size]).collect();
+            graph.output_buffers[index] = self.processor_types[index].1.iter()
+                .map(|signal| vec![*signal; block_size]).collect();
+            graph.processors[index] = Option::Some(processor);
+        }
+        graph.adjacency_list = adj_clone(&self.adjacency_list);
+        graph.topologically_ordered = topo_clone(&self.topologically_ordered).unwrap();
+        graph.io = io_clone(&self.ios);
+        graph.feedback = self.feedback.clone();
+        graph.resets = resets;
+        self.send(CallbackMessage::Graph(graph))
+    }
+
+    /// renders `frames` frames with the engine created by `run_offline`.
+    pub fn render(&mut self, frames: usize) -> Result<Vec<f32>, MeshError> {
+        match self.offline {
+            Some(ref mut engine) => Ok(engine.render(frames)),
+            None                 => Err(MeshError::NotRunning),
+        }
+    }
+
+
     pub fn new_connection(&mut self, in_proc: usize, in_plug: usize,
-                          out_proc: usize, out_plug: usize) -> bool {
+                          out_proc: usize, out_plug: usize) -> Result<(), MeshError> {
+        self.new_converting_connection((in_proc, in_plug), (out_proc, out_plug)).map(|_| ())
+    }
 
-        {
-            if !self.connect((in_proc, in_plug), (out_proc, out_plug)) { 
-                return false;
+    /// with auto conversion on, connecting plugs of different types puts a
+    /// converter between them instead of failing, if there is one for the
+    /// types, see `convert::converter`. it is off by default.
+    pub fn set_auto_convert(&mut self, on: bool) {
+        self.auto_convert = on;
+    }
+
+    /// like `new_connection`, but returns the index of the converter that
+    /// was put between the plugs, if one was.
+    pub fn new_converting_connection(&mut self, output: (usize, usize), input: (usize, usize))
+                                     -> Result<Option<usize>, MeshError> {
+        match self.check_plugs(output, input) {
+            Err(MeshError::TypeMismatch(_, _)) if self.auto_convert => (),
+            _ => {
+                try!(self.connect(output, input));
+                try!(self.send_graph(Vec::new(), Vec::new()));
+                return Ok(Option::None);
+            },
+        }
+        let mut converter = match convert::converter(&self.processor_types[output.0].1[output.1],
+                                                     &self.processor_types[input.0].0[input.1]) {
+            Some(converter) => converter,
+            None            => return Err(MeshError::TypeMismatch(output, input)),
+        };
+        if self.reaches(input.0, output.0) {
+            return Err(MeshError::Cycle(output, input));
+        }
+        converter.prepare(self.config.sample_rate, self.config.frames_per_buffer);
+        let converter = self.register_processor(converter);
+        let index = self.processor_types.len() - 1;
+        // can't fail, the converter fits both plugs and closes no cycle.
+        self.connect(output, (index, 0)).ok();
+        self.connect((index, 0), input).ok();
+        try!(self.send_graph(vec![(index, converter)], Vec::new()));
+        Ok(Option::Some(index))
+    }
+
+    // whether there is a path of connections from processor `from` to `to`,
+    // leaving out feedback connections.
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut visited = vec![false; self.adjacency_list.len()];
+        let mut to_visit = vec![from];
+        while let Some(current) = to_visit.pop() {
+            if current == to {
+                return true;
             }
-            match self.topologically_ordered {
-                Some(_) =>  (),
-                None    =>  return false,
+            if visited[current] {
+                continue;
             }
-            if self.tx.is_none() {
-                return false;
+            visited[current] = true;
+            for out_plug in &self.adjacency_list[current] {
+                to_visit.extend(out_plug.iter().map(|&(in_processor, _)| in_processor));
             }
         }
-        let adj_list_clone: AdjList; 
-        let topo_list_clone: TopoList;
-        let ios_list_clone: IoList ;
-        {
-            adj_list_clone = adj_clone(&self.adjacency_list);
-            topo_list_clone = topo_clone(&self.topologically_ordered);
-            ios_list_clone = io_clone(&self.ios);
-        }
-        match (*self).tx {
-            Some(ref a) => a.send(
-                CallbackMessage::Connections(adj_list_clone,
-                                             topo_list_clone.unwrap(),
-                                             ios_list_clone)).unwrap(),
-            None    => return false,
-        }
-        return true;
-    }
-
-    pub fn set_constant(&mut self, index: usize, value: f64) {
-        match (*self).tx {
-            Some(ref a) => a.send(CallbackMessage::Constant(
-                    index,
-                    value)).unwrap(),
+        false
+    }
+
+    /// removes the connection from `output` to `input`, both given as
+    /// (processor, plug), and resets `input` to its default value if nothing
+    /// else is connected to it.
+    pub fn disconnect(&mut self, output: (usize, usize), input: (usize, usize))
+                      -> Result<(), MeshError> {
+        let position;
+        match self.adjacency_list.get(output.0).and_then(|plugs| plugs.get(output.1)) {
+            Some(connections) => position = connections.iter().position(|c| *c == input),
+            None              => return Err(MeshError::NoSuchConnection(output, input)),
+        }
+        match position {
+            Some(i) => { self.adjacency_list[output.0][output.1].remove(i); },
+            None    => match self.feedback.iter().position(|f| *f == (output, input)) {
+                Some(i) => { self.feedback.remove(i); },
+                None    => return Err(MeshError::NoSuchConnection(output, input)),
+            },
+        }
+        self.order_topologically();
+
+        let resets = self.unconnected_defaults(&vec![input]);
+        self.send_graph(Vec::new(), resets)
+    }
+
+    /// the constant jumps to `value` right away.
+    pub fn set_constant(&mut self, index: usize, value: f64) -> Result<(), MeshError> {
+        self.update_constant(index, value, 0.0)
+    }
+
+    /// like `set_constant`, but the constant ramps to `value` over `ramp`
+    /// seconds.
+    pub fn set_constant_ramped(&mut self, index: usize, value: f64, ramp: f64)
+                               -> Result<(), MeshError> {
+        self.update_constant(index, value, ramp)
+    }
+
+    fn update_constant(&mut self, index: usize, value: f64, ramp: f64)
+                       -> Result<(), MeshError> {
+        if index >= self.processor_types.len() {
+            return Err(MeshError::NoSuchProcessor(index));
+        }
+        if self.processor_types[index].2 != "Constant" {
+            return Err(MeshError::NotAConstant(index));
+        }
+        match self.input_buffers[index].get_mut(0) {
+            Some(input) => *input = Signal::Sound(value),
             None        => (),
         }
+        match self.input_buffers[index].get_mut(1) {
+            Some(input) => *input = Signal::Sound(ramp),
+            None        => (),
+        }
+        if self.tx.is_none() {
+            return Ok(());
+        }
+        self.send(CallbackMessage::Constant(index, value, ramp))
     }
 
-    pub fn new_processor(&mut self, processor: Box<Processor>) {
-        let mut unpacked_tx;
-        let message = CallbackMessage::Processor(self.register_processor(processor));
-        match self.tx {
-            Some(ref a) => unpacked_tx = a,
-            None    => return (),
+    /// returns the index of the new processor.
+    pub fn new_processor(&mut self, mut processor: Box<Processor>) -> Result<usize, MeshError> {
+        // prepared first, so the ports it describes fit the sample rate.
+        processor.prepare(self.config.sample_rate, self.config.frames_per_buffer);
+        let processor = self.register_processor(processor);
+        let index = self.processor_types.len() - 1;
+        try!(self.send_graph(vec![(index, processor)], Vec::new()));
+        Ok(index)
+    }
+
+    /// removes a processor together with all connections from and to it.
+    /// its index stays taken by a `Dummy`, so the indices of the other
+    /// processors don't change.
+    pub fn delete_processor(&mut self, processor: usize) -> Result<(), MeshError> {
+        if processor >= self.adjacency_list.len()
+            || self.processor_types[processor].2 == "Dummy" {
+            return Err(MeshError::NoSuchProcessor(processor));
+        }
+        let mut disconnected: Vec<(usize, usize)> = Vec::new();
+        for out_plug in &self.adjacency_list[processor] {
+            for connection in out_plug {
+                disconnected.push(*connection);
+            }
         }
-        unpacked_tx.send(message);
+        self.adjacency_list[processor] = Vec::new();
+        for other in self.adjacency_list.iter_mut() {
+            for out_plug in other.iter_mut() {
+                out_plug.retain(|&(in_processor, _)| in_processor != processor);
+            }
+        }
+        self.ios.retain(|&io| io != processor);
+        for &(output, input) in &self.feedback {
+            if output.0 == processor {
+                disconnected.push(input);
+            }
+        }
+        self.feedback.retain(|&(output, input)| output.0 != processor && input.0 != processor);
+        self.input_buffers[processor] = Vec::new();
+        self.processor_types[processor] = (Vec::new(), Vec::new(), String::from("Dummy"));
+        self.arguments[processor] = Option::Some(Vec::new());
+        self.ports[processor] = (Vec::new(), Vec::new());
+        self.names[processor] = String::new();
+        self.order_topologically();
+
+        // the engine takes the deleted processor's place in the new graph
+        // with a `Dummy`, so the processor is freed with the old graph.
+        let resets = self.unconnected_defaults(&disconnected);
+        self.unsent.retain(|&(index, _)| index != processor);
+        self.send_graph(vec![(processor, Box::new(Dummy::new()))], resets)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), PatchError> {
+        let text = try!(try!(self.to_patch()).to_toml());
+        try!(fs::write(path, text));
+        Ok(())
+    }
+
+    /// replaces everything in the mesh with the patch in the file at `path`.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), PatchError> {
+        let text = try!(fs::read_to_string(path));
+        let patch = try!(Patch::from_toml(&text));
+        self.apply_patch(patch)
     }
 
-    pub fn delete_processor(&mut self, processor: usize) {
+    /// fails for processors that can't be built from arguments.
+    pub fn to_patch(&self) -> Result<Patch, MeshError> {
+        let mut patch = Patch {
+            processors: Vec::new(),
+            connections: Vec::new(),
+            feedback: Vec::new(),
+        };
+        for (index, types) in self.processor_types.iter().enumerate() {
+            // deleted processors have no name.
+            let name = if self.names[index].is_empty() {
+                Option::None
+            } else {
+                Option::Some(self.names[index].clone())
+            };
+            patch.processors.push(PatchProcessor {
+                type_name: types.2.clone(),
+                name: name,
+                arguments: try!(self.arguments[index].clone()
+                    .ok_or(MeshError::NoArguments(index))),
+                inputs: self.input_buffers[index].iter().map(|signal| signal.value()).collect(),
+            });
+        }
+        for (processor_num, processor) in self.adjacency_list.iter().enumerate() {
+            for (out_plug, connections) in processor.iter().enumerate() {
+                for &input in connections {
+                    patch.connections.push(PatchConnection {
+                        from: (processor_num, out_plug),
+                        to: input,
+                    });
+                }
+            }
+        }
+        for &(output, input) in &self.feedback {
+            patch.feedback.push(PatchConnection { from: output, to: input });
+        }
+        Ok(patch)
     }
 
-    pub fn prompt(&mut self) {
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Result::Ok(_)  => (),
-            Result::Err(_) => {
-                println!("could not read line");
+    /// replaces everything in the mesh with `patch`. the patch is built up
+    /// on its own first, so if it is invalid the mesh stays as it was. the
+    /// engine gets the new graph in one go.
+    pub fn apply_patch(&mut self, patch: Patch) -> Result<(), PatchError> {
+        let mut loaded = Mesh::checked(self.config);
+        let mut names = Vec::new();
+        for description in patch.processors {
+            // deleted processors keep their index, see `delete_processor`.
+            if description.type_name == "Dummy" {
+                let index = try!(loaded.new_processor(Box::new(Dummy::new())));
+                loaded.names[index] = String::new();
+                continue;
+            }
+            let arguments: Vec<&str> = description.arguments.iter().map(|a| a.as_str()).collect();
+            let processor = try!(self.registry.create(&description.type_name, &arguments));
+            let index = try!(loaded.new_processor(processor));
+            match description.name {
+                Some(name) => names.push((index, name)),
+                None       => (),
+            }
+            for (plug, value) in description.inputs.into_iter().enumerate() {
+                match loaded.input_buffers[index].get_mut(plug) {
+                    Some(input) => *input = input.with_value(value),
+                    None => return Err(PatchError::Mesh(MeshError::NoSuchInput(index, plug))),
+                }
+            }
+        }
+        // named processors give up their default names first, so they can
+        // take each other's.
+        for &(index, _) in &names {
+            loaded.names[index] = String::new();
+        }
+        for (index, name) in names {
+            try!(loaded.check_name(index, &name));
+            loaded.names[index] = name;
+        }
+        for connection in patch.connections {
+            try!(loaded.connect(connection.from, connection.to));
+        }
+        for connection in patch.feedback {
+            try!(loaded.connect_feedback(connection.from, connection.to));
+        }
+
+        self.processor_types = loaded.processor_types;
+        self.arguments = loaded.arguments;
+        self.ports = loaded.ports;
+        self.names = loaded.names;
+        self.input_buffers = loaded.input_buffers;
+        self.adjacency_list = loaded.adjacency_list;
+        self.topologically_ordered = loaded.topologically_ordered;
+        self.ios = loaded.ios;
+        self.feedback = loaded.feedback;
+        // every processor is new, so the engine drops all the old ones.
+        self.unsent = Vec::new();
+        let new = mem::replace(&mut loaded.unsent, Vec::new());
+        try!(self.send_graph(new, Vec::new()));
+        Ok(())
+    }
+
+    /// of the given input plugs, returns those that no connection leads to
+    /// anymore, together with their default values.
+    fn unconnected_defaults(&self, plugs: &Vec<(usize, usize)>) -> Vec<(usize, usize, Signal)> {
+        let mut defaults = Vec::new();
+        for &(in_processor, in_plug) in plugs {
+            if self.is_connected((in_processor, in_plug)) {
+                continue;
+            }
+            match self.processor_types[in_processor].0.get(in_plug) {
+                Some(default) => defaults.push((in_processor, in_plug, default.clone())),
+                None          => (),
+            }
+        }
+        defaults
+    }
+
+    fn is_connected(&self, input: (usize, usize)) -> bool {
+        self.adjacency_list.iter()
+            .any(|processor| processor.iter().any(|out_plug| out_plug.contains(&input)))
+            || self.feedback.iter().any(|&(_, other)| other == input)
+    }
+
+    /// every processor as "index: name (type)", one per line.
+    pub fn list(&self) -> String {
+        let mut text = String::new();
+        for (index, types) in self.processor_types.iter().enumerate() {
+            if self.names[index].is_empty() {
+                continue;
+            }
+            text.push_str(&format!("{}: {} ({})\n", index, self.names[index], types.2));
+        }
+        text
+    }
+
+    /// the inputs of a processor with their types and defaults, and their
+    /// current values where those differ, and the outputs with their types,
+    /// together with what the ports say about them.
+    pub fn describe_ports(&self, processor: usize) -> Result<String, MeshError> {
+        let (inputs, outputs) = try!(self.ports(processor));
+        let mut text = String::from("inputs:\n");
+        for (plug, port) in inputs.iter().enumerate() {
+            let default = self.processor_types[processor].0[plug];
+            let value = self.input_buffers[processor][plug];
+            let current = if value == default {
+                String::new()
+            } else {
+                format!(" (now {})", value.value())
+            };
+            text.push_str(&format!("  {} {}: {}{}{}\n", plug, port.name, default, current,
+                                   describe_port(port)));
+        }
+        text.push_str("outputs:\n");
+        for (plug, port) in outputs.iter().enumerate() {
+            let kind = self.processor_types[processor].1[plug].type_name();
+            text.push_str(&format!("  {} {}: {}{}\n", plug, port.name, kind, describe_port(port)));
+        }
+        Ok(text)
+    }
+
+    /// every connection as "output -> input", one per line.
+    pub fn edges(&self) -> String {
+        let mut text = String::new();
+        for (processor_num, processor) in self.adjacency_list.iter().enumerate() {
+            for (out_plug, connections) in processor.iter().enumerate() {
+                for &input in connections {
+                    text.push_str(&format!("{} -> {}\n",
+                                           self.plug_name((processor_num, out_plug), true),
+                                           self.plug_name(input, false)));
+                }
+            }
+        }
+        for &(output, input) in &self.feedback {
+            text.push_str(&format!("{} -> {} (feedback)\n",
+                                   self.plug_name(output, true), self.plug_name(input, false)));
+        }
+        text
+    }
+
+    /// the processors in the order the engine runs them.
+    pub fn order(&self) -> String {
+        match self.topologically_ordered {
+            Some(ref order) => order.iter()
+                .filter(|&&index| !self.names[index].is_empty())
+                .map(|&index| format!("{}: {}\n", index, self.names[index]))
+                .collect(),
+            None => String::from("the processors form a cycle\n"),
+        }
+    }
+
+    // "name.plug (processor.plug)"
+    fn plug_name(&self, plug: (usize, usize), output: bool) -> String {
+        let ports = if output { &self.ports[plug.0].1 } else { &self.ports[plug.0].0 };
+        format!("{}.{} ({}.{})", self.names[plug.0], ports[plug.1].name, plug.0, plug.1)
+    }
+
+    /// reads commands from the terminal until `quit` or the end of input,
+    /// with line editing and history. see `command::parse` for the syntax.
+    pub fn repl(&mut self) {
+        let mut editor = match DefaultEditor::new() {
+            Ok(editor) => editor,
+            Err(err)   => {
+                println!("could not open the prompt: {}", err);
                 return ();
             },
+        };
+        loop {
+            let line = match editor.readline("> ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+                Err(err) => {
+                    println!("could not read line: {}", err);
+                    break;
+                },
+            };
+            if !line.trim().is_empty() {
+                editor.add_history_entry(line.as_str()).ok();
+            }
+            match command::parse(&line) {
+                Ok(Some(Command::Quit)) => break,
+                Ok(Some(command)) => match self.execute(command) {
+                    Ok(text) => print!("{}", text),
+                    Err(err) => println!("{}", err),
+                },
+                Ok(None) => (),
+                Err(err) => println!("{}", err),
+            }
+        }
+    }
+
+    /// parses and executes a line of the prompt. returns what the command
+    /// has to say, e.g. the processors for `list`.
+    pub fn run_command(&mut self, line: &str) -> Result<String, CommandError> {
+        match try!(command::parse(line)) {
+            Some(command) => self.execute(command),
+            None          => Ok(String::new()),
         }
-        let inputs: Vec<&str> = input.trim_right().split(' ').collect();
-        match inputs[0] {
-            "new" => {
-                let mut processor: Box<Processor>;
-                match inputs[1].trim_right() {
-                    "constant" => processor = Box::new(Constant::new()),
-                    "sine" => processor = Box::new(Sine::new()),
-                    "add"  => processor = Box::new(Add::new()),
-                    "mult" => processor = Box::new(Mult::new()),
-                    "dac"  => processor = Box::new(Dac::new()),
-                    x      => {
-                                println!("module \"{}\" not known", x);
-                                return ();
-                               },
+    }
+
+    /// runs `script` one line at a time, with the same commands as the
+    /// prompt, and returns what they had to say. stops at the first line
+    /// that fails or at `quit`.
+    pub fn run_script(&mut self, script: &str) -> Result<String, ScriptError> {
+        let mut output = String::new();
+        for (number, line) in script.lines().enumerate() {
+            let result = match command::parse(line) {
+                Ok(Some(Command::Quit)) => break,
+                Ok(Some(command))       => self.execute(command),
+                Ok(None)                => continue,
+                Err(err)                => Err(err),
+            };
+            match result {
+                Ok(text) => output.push_str(&text),
+                Err(err) => return Err(ScriptError::Line(number + 1, err)),
+            }
+        }
+        Ok(output)
+    }
+
+    /// like `run_script`. scripts may `run` other scripts, but not one
+    /// that is already running.
+    pub fn run_script_file<P: AsRef<Path>>(&mut self, path: P) -> Result<String, ScriptError> {
+        let path = try!(fs::canonicalize(path));
+        if self.scripts.contains(&path) {
+            return Err(ScriptError::Recursion(path));
+        }
+        let script = try!(fs::read_to_string(&path));
+        self.scripts.push(path);
+        let result = self.run_script(&script);
+        self.scripts.pop();
+        result
+    }
+
+    pub fn execute(&mut self, command: Command) -> Result<String, CommandError> {
+        match command {
+            Command::New(name, arguments, label) => {
+                let arguments: Vec<&str> = arguments.iter().map(|a| a.as_str()).collect();
+                let processor = try!(self.registry.create(&name, &arguments));
+                match label {
+                    Some(ref label) => try!(self.check_name(self.names.len(), label)),
+                    None            => (),
                 }
-                self.new_processor(processor);
+                let index = try!(self.new_processor(processor));
+                match label {
+                    Some(label) => self.names[index] = label,
+                    None        => (),
+                }
+                Ok(format!("{}: {}\n", index, self.names[index]))
+            },
+            Command::Delete(processor) => {
+                let processor = try!(self.processor_index(&processor));
+                try!(self.delete_processor(processor));
+                Ok(String::new())
+            },
+            Command::Name(processor, name) => {
+                let processor = try!(self.processor_index(&processor));
+                try!(self.rename_processor(processor, &name));
+                Ok(String::new())
+            },
+            Command::Connect(output, input) => {
+                match try!(self.connect_by_name(&output, &input)) {
+                    Some(index) => Ok(format!("inserted {}: {} ({})\n", index, self.names[index],
+                                              self.processor_types[index].2)),
+                    None        => Ok(String::new()),
+                }
+            },
+            Command::AutoConvert(on) => {
+                self.set_auto_convert(on);
+                Ok(String::new())
+            },
+            Command::Feedback(output, input) => {
+                let output = try!(self.find_output(&output));
+                let input = try!(self.find_input(&input));
+                try!(self.new_feedback_connection(output, input));
+                Ok(String::new())
+            },
+            Command::Disconnect(output, input) => {
+                let output = try!(self.find_output(&output));
+                let input = try!(self.find_input(&input));
+                try!(self.disconnect(output, input));
+                Ok(String::new())
+            },
+            Command::Constant(processor, value, ramp) => {
+                let processor = try!(self.processor_index(&processor));
+                try!(self.update_constant(processor, value, ramp.unwrap_or(0.0)));
+                Ok(String::new())
             },
-            "connect" => {
-                let c1 = inputs[1].parse::<usize>().unwrap();
-                let c2 = inputs[2].parse::<usize>().unwrap();
-                let c3 = inputs[3].parse::<usize>().unwrap();
-                let c4 = inputs[4].trim_right().parse::<usize>().unwrap();
-                if !self.new_connection(c1, c2, c3, c4) {
-                    println!("types dont match");
-                } 
+            Command::List  => Ok(self.list()),
+            Command::Ports(processor) => {
+                let processor = try!(self.processor_index(&processor));
+                Ok(try!(self.describe_ports(processor)))
             },
-            "constant" => {
-                self.set_constant(inputs[1].parse().unwrap(), inputs[2].parse().unwrap());
+            Command::Edges => Ok(self.edges()),
+            Command::Order => Ok(self.order()),
+            Command::Save(path) => {
+                try!(self.save(path));
+                Ok(String::new())
             },
-            _ => println!("command not found"),
+            Command::Load(path) => {
+                try!(self.load(path));
+                Ok(String::new())
+            },
+            Command::Run(path) => self.run_script_file(path)
+                .map_err(|err| CommandError::Script(Box::new(err))),
+            Command::Help  => Ok(command::help()),
+            Command::Quit  => Ok(String::new()),
         }
     }
 
-    pub fn connect(self: &mut Mesh, output: (usize, usize), input: (usize, usize)) -> bool {
+    /// adds a connection without telling the engine.
+    pub fn connect(self: &mut Mesh, output: (usize, usize), input: (usize, usize))
+                   -> Result<(), MeshError> {
     // output: (processor, plug), input: (processor, plug)
-	match self.topologically_ordered {
-	    Option::Some(_) => (),
-	    Option::None => return false,
-	}
+        try!(self.check_plugs(output, input));
         self.adjacency_list[output.0][output.1].push((input.0, input.1));
-        let connections_match = self.check_types();
-        if connections_match {
-            self.order_topologically();
-            true
-        } else {
-            self.adjacency_list[output.0][output.1].pop();
-            false
+        self.order_topologically();
+        if self.topologically_ordered.is_some() {
+            return Ok(());
         }
+        // the connection closed a cycle, those need a feedback connection.
+        self.adjacency_list[output.0][output.1].pop();
+        self.order_topologically();
+        Err(MeshError::Cycle(output, input))
+    }
+
+    /// connects `output` to `input` with a delay of one block. feedback
+    /// connections are left out when ordering the processors, so they may
+    /// close cycles. `input` receives what `output` produced in the previous
+    /// block.
+    pub fn connect_feedback(&mut self, output: (usize, usize), input: (usize, usize))
+                            -> Result<(), MeshError> {
+        try!(self.check_plugs(output, input));
+        self.feedback.push((output, input));
+        Ok(())
+    }
+
+    /// like `connect_feedback`, but also tells the engine.
+    pub fn new_feedback_connection(&mut self, output: (usize, usize), input: (usize, usize))
+                                   -> Result<(), MeshError> {
+        try!(self.connect_feedback(output, input));
+        self.send_graph(Vec::new(), Vec::new())
     }
 
     pub fn order_topologically(self: &mut Mesh) {
@@ -320,22 +1254,18 @@ impl Mesh {
         let mut count = 0;
 
         while visited.len() < self.adjacency_list.len() {
-            for (processor, connections) in 
-                    incoming_connections.iter().enumerate() {
-
-                if !visited.contains_key(&processor) && connections.len() == 0  {
-                    to_visit.push_front(processor);
-                }
-            }
-            
             let current: usize;
             match to_visit.pop_front() {
                 Some(x) => current = x,
-                None => panic!(),
+                // everything that is left is part of a cycle.
+                None => break,
             }
             let outgoing: &HashMap<usize, usize> = &outgoing_connections[current];
             for (new, _) in outgoing {
-                incoming_connections[*new].remove(&current);    
+                incoming_connections[*new].remove(&current);
+                if incoming_connections[*new].len() == 0 {
+                    to_visit.push_back(*new);
+                }
             }
 
             visited.insert(current, 0);
@@ -350,34 +1280,140 @@ impl Mesh {
         
     }
 
-    fn check_types(self: &Mesh) -> bool {
-        for (processor_num, processor) in self.adjacency_list.iter().enumerate() {
-            for (out_plug_num, out_plug) in processor.iter().enumerate() {
-                for in_processor in out_plug {
-                    let (in_processor_num, in_plug) = *in_processor;
-                    let this_plug  = &self.processor_types[processor_num]
-                        .1[out_plug_num];
-                    let other_plug = &self.processor_types[in_processor_num]
-                        .0[in_plug];
-                    let return_value: bool;
-                    match *this_plug {
-                        Signal::Sound(_) => match *other_plug {
-                            Signal::Sound(_) => return_value = true,
-                            Signal::Int(_)   => return_value = false,
-                        },
-                        Signal::Int(_)   => match *other_plug {
-                            Signal::Sound(_) => return_value = false,
-                            Signal::Int(_)   => return_value = true,
-                        }
-                    }
-                    if !return_value {
-                        return false;
-                    }
-                }
-            }
+    /// the name a processor can be addressed by, e.g. "sine" in "sine.freq".
+    pub fn processor_name(&self, processor: usize) -> Result<&str, MeshError> {
+        match self.names.get(processor) {
+            Some(name) if !name.is_empty() => Ok(name),
+            _ => Err(MeshError::NoSuchProcessor(processor)),
         }
-        true
     }
+
+    /// names must be unique, must not contain dots or whitespace and must
+    /// not be a number, as numbers are taken to be indices.
+    pub fn rename_processor(&mut self, processor: usize, name: &str) -> Result<(), MeshError> {
+        try!(self.processor_name(processor));
+        try!(self.check_name(processor, name));
+        self.names[processor] = name.to_string();
+        Ok(())
+    }
+
+    fn check_name(&self, processor: usize, name: &str) -> Result<(), MeshError> {
+        if name.is_empty() || name.contains('.') || name.contains(char::is_whitespace)
+            || name.parse::<usize>().is_ok()
+            || self.names.iter().enumerate().any(|(i, other)| i != processor && other == name) {
+            return Err(MeshError::InvalidName(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// (inputs, outputs) of a processor.
+    pub fn ports(&self, processor: usize) -> Result<(&[Port], &[Port]), MeshError> {
+        try!(self.processor_name(processor));
+        let ports = &self.ports[processor];
+        Ok((&ports.0, &ports.1))
+    }
+
+    /// `base`, or `base` with the lowest number from 2 up that is not taken.
+    fn free_name(&self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut number = 2;
+        while self.names.contains(&name) {
+            name = format!("{}{}", base, number);
+            number += 1;
+        }
+        name
+    }
+
+    /// the index of the processor called `name`, which may also be an index.
+    pub fn processor_index(&self, name: &str) -> Result<usize, MeshError> {
+        self.find_processor(name).ok_or_else(|| MeshError::UnknownName(name.to_string()))
+    }
+
+    fn find_processor(&self, name: &str) -> Option<usize> {
+        match self.names.iter().position(|other| other == name) {
+            Some(index) => Option::Some(index),
+            None => name.parse::<usize>().ok()
+                .and_then(|index| self.processor_name(index).ok().map(|_| index)),
+        }
+    }
+
+    // looks up "processor.plug", where both parts are names or indices.
+    fn find_plug(&self, name: &str, output: bool) -> Result<(usize, usize), MeshError> {
+        let unknown = || MeshError::UnknownName(name.to_string());
+        let mut parts = name.splitn(2, '.');
+        let processor_name = parts.next().unwrap_or("");
+        let plug_name = try!(parts.next().ok_or_else(&unknown));
+        let processor = try!(self.find_processor(processor_name).ok_or_else(&unknown));
+        let ports = if output { &self.ports[processor].1 } else { &self.ports[processor].0 };
+        match ports.iter().position(|port| port.name == plug_name) {
+            Some(plug) => Ok((processor, plug)),
+            None => match plug_name.parse::<usize>() {
+                Ok(plug) if plug < ports.len() => Ok((processor, plug)),
+                _ => Err(unknown()),
+            },
+        }
+    }
+
+    /// resolves an output plug name like "sine.out" or "3.0".
+    pub fn find_output(&self, name: &str) -> Result<(usize, usize), MeshError> {
+        self.find_plug(name, true)
+    }
+
+    /// resolves an input plug name like "sine.freq" or "3.0".
+    pub fn find_input(&self, name: &str) -> Result<(usize, usize), MeshError> {
+        self.find_plug(name, false)
+    }
+
+    /// like `new_converting_connection`, with plugs given by name, e.g.
+    /// `connect_by_name("lfo.out", "sine.freq")`.
+    pub fn connect_by_name(&mut self, output: &str, input: &str)
+                           -> Result<Option<usize>, MeshError> {
+        let output = try!(self.find_output(output));
+        let input = try!(self.find_input(input));
+        self.new_converting_connection(output, input)
+    }
+
+    /// checks that both plugs exist and carry the same type of signal.
+    fn check_plugs(&self, output: (usize, usize), input: (usize, usize))
+                   -> Result<(), MeshError> {
+        let this_plug;
+        match self.processor_types.get(output.0) {
+            Some(types) => match types.1.get(output.1) {
+                Some(plug) => this_plug = plug,
+                None       => return Err(MeshError::NoSuchOutput(output.0, output.1)),
+            },
+            None => return Err(MeshError::NoSuchProcessor(output.0)),
+        }
+        let other_plug;
+        match self.processor_types.get(input.0) {
+            Some(types) => match types.0.get(input.1) {
+                Some(plug) => other_plug = plug,
+                None       => return Err(MeshError::NoSuchInput(input.0, input.1)),
+            },
+            None => return Err(MeshError::NoSuchProcessor(input.0)),
+        }
+        if this_plug.same_type(other_plug) {
+            Ok(())
+        } else {
+            Err(MeshError::TypeMismatch(output, input))
+        }
+    }
+}
+
+// unit, range and description, as far as the port has them.
+fn describe_port(port: &Port) -> String {
+    let mut text = String::new();
+    if !port.unit.is_empty() {
+        text.push_str(&format!(" {}", port.unit));
+    }
+    match port.range {
+        Some((min, max)) => text.push_str(&format!(" [{}, {}]", min, max)),
+        None             => (),
+    }
+    if !port.description.is_empty() {
+        text.push_str(&format!(", {}", port.description));
+    }
+    text
 }
 
 fn contains(list: &LinkedList<usize>, other: &usize) -> bool {
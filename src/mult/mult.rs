@@ -1,6 +1,7 @@
 pub struct Mult;
 use mesh::mesh::Signal;
 use mesh::mesh::Processor;
+use mesh::mesh::Port;
 
 impl Processor for Mult {
     fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
@@ -8,16 +9,33 @@ impl Processor for Mult {
         let b: f64;
         match input[0] {
             Signal::Sound(x) => a = x,
-            Signal::Int(_)   => panic!(),
+            _                => panic!(),
         }
 
         match input[1] {
             Signal::Sound(x) => b = x,
-            Signal::Int(_)   => panic!(),
+            _                => panic!(),
         }
         vec![Signal::Sound(a * b)]
     }
 
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            let a: f64;
+            let b: f64;
+            match input[0][frame] {
+                Signal::Sound(x) => a = x,
+                _                => panic!(),
+            }
+            match input[1][frame] {
+                Signal::Sound(x) => b = x,
+                _                => panic!(),
+            }
+            output[0][frame] = Signal::Sound(a * b);
+        }
+    }
+
     fn input_types_and_defaults(& self) -> Vec<Signal> {
         vec![Signal::Sound(0.0), Signal::Sound(0.0)]
     }
@@ -26,6 +44,14 @@ impl Processor for Mult {
         vec![Signal::Sound(0.0)]
     }
 
+    fn input_ports(&self) -> Vec<Port> {
+        vec![Port::new("a", "first factor"), Port::new("b", "second factor")]
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("product", "a * b")]
+    }
+
     fn type_name(&self) -> String {
         String::from("Mult")
     }
@@ -0,0 +1,140 @@
+use mesh::mesh::Signal;
+use mesh::mesh::Processor;
+use mesh::mesh::Port;
+
+/// the spectrum of a `Noise`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    // the same power at every frequency.
+    White,
+    // 3 dB less power per octave.
+    Pink,
+    // 6 dB less power per octave, a random walk.
+    Brown,
+}
+
+impl Color {
+    pub fn from_name(name: &str) -> Option<Color> {
+        match name {
+            "white" => Option::Some(Color::White),
+            "pink"  => Option::Some(Color::Pink),
+            "brown" => Option::Some(Color::Brown),
+            _       => Option::None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Color::White => "white",
+            Color::Pink  => "pink",
+            Color::Brown => "brown",
+        }
+    }
+}
+
+/// noise that is the same every time for the same seed, so offline renders
+/// can be reproduced. a trigger on the reset input starts it over.
+pub struct Noise {
+    color: Color,
+    seed: u64,
+    // xorshift state, never 0.
+    state: u64,
+    // the filter states of pink and brown noise.
+    filter: [f64; 7],
+}
+
+impl Processor for Noise {
+    fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
+        vec![Signal::Sound(self.next(input[0]))]
+    }
+
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            output[0][frame] = Signal::Sound(self.next(input[0][frame]));
+        }
+    }
+
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        vec![Signal::Trigger(false)]
+    }
+
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Sound(0.0)]
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        vec![Port::new("reset", "starts the noise over from its seed")]
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("out", "noise, mostly within [-1, 1]")]
+    }
+
+    fn type_name(&self) -> String {
+        String::from("Noise")
+    }
+
+    fn arguments(&self) -> Option<Vec<String>> {
+        Option::Some(vec![self.color.name().to_string(), self.seed.to_string()])
+    }
+}
+
+impl Noise {
+    pub fn new(color: Color) -> Noise {
+        Noise::with_seed(color, 0)
+    }
+
+    pub fn with_seed(color: Color, seed: u64) -> Noise {
+        let mut noise = Noise { color: color, seed: seed, state: 1, filter: [0.0; 7] };
+        noise.reset();
+        noise
+    }
+
+    fn reset(&mut self) {
+        // splitmix64, so that close seeds give unrelated sequences.
+        let mut z = self.seed.wrapping_add(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+        self.state = if z == 0 { 1 } else { z };
+        self.filter = [0.0; 7];
+    }
+
+    // uniformly distributed in [-1, 1), xorshift64*.
+    fn white(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let bits = self.state.wrapping_mul(0x2545F4914F6CDD1D) >> 11;
+        bits as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
+
+    fn next(&mut self, reset: Signal) -> f64 {
+        if reset == Signal::Trigger(true) {
+            self.reset();
+        }
+        let white = self.white();
+        let b = &mut self.filter;
+        match self.color {
+            Color::White => white,
+            // Paul Kellet's filter, accurate to 0.05 dB above 9 Hz at 44.1 kHz.
+            Color::Pink => {
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.1538520;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+                pink * 0.11
+            },
+            // a leaky integrator, so it doesn't wander off.
+            Color::Brown => {
+                b[0] = (b[0] + 0.02 * white) / 1.02;
+                b[0] * 3.5
+            },
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use mesh::mesh::Signal;
+use mesh::mesh::Processor;
+use mesh::mesh::Port;
+use mesh::mesh::SAMPLERATE;
+
+// the jumps and corners of the naive waveforms are smoothed over the two
+// samples around them with polynomial approximations of a band limited step
+// (PolyBLEP) and ramp (PolyBLAMP). this removes most of the aliasing at a
+// fraction of the cost of a wavetable.
+
+// `t` is the phase in [0, 1), `dt` the phase increment per frame.
+fn poly_blep(t: f64, dt: f64) -> f64 {
+    if t < dt {
+        let t = t / dt;
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+// the integrated step, for corners where the slope jumps but the value
+// doesn't. it is cubic, so it is scaled with the slope and `dt`.
+fn poly_blamp(t: f64, dt: f64) -> f64 {
+    if t < dt {
+        let t = t / dt - 1.0;
+        -t * t * t / 3.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt + 1.0;
+        t * t * t / 3.0
+    } else {
+        0.0
+    }
+}
+
+fn wrap(phase: f64) -> f64 {
+    phase - phase.floor()
+}
+
+// 1 for the first `width` of the period, -1 for the rest.
+fn pulse(t: f64, dt: f64, width: f64) -> f64 {
+    let naive = if t < width { 1.0 } else { -1.0 };
+    naive + poly_blep(t, dt) - poly_blep(wrap(t + 1.0 - width), dt)
+}
+
+/// the shapes an `Oscillator` can have.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    // rising from -1 to 1.
+    Saw,
+    // 1 for the first half of the period, -1 for the second.
+    Square,
+    // -1 at the start of the period, 1 halfway.
+    Triangle,
+    // 1 for the part of the period the width input says, -1 for the rest.
+    Pulse,
+}
+
+impl Waveform {
+    /// the type name of an oscillator with this waveform.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Waveform::Saw      => "Saw",
+            Waveform::Square   => "Square",
+            Waveform::Triangle => "Triangle",
+            Waveform::Pulse    => "Pulse",
+        }
+    }
+
+    fn value(&self, t: f64, dt: f64, width: f64) -> f64 {
+        match *self {
+            Waveform::Saw      => 2.0 * t - 1.0 - poly_blep(t, dt),
+            Waveform::Square   => pulse(t, dt, 0.5),
+            Waveform::Triangle => {
+                let naive = 1.0 - 4.0 * (t - 0.5).abs();
+                // the slope changes by 8 per period at both corners.
+                naive + 4.0 * dt * (poly_blamp(t, dt) - poly_blamp(wrap(t + 0.5), dt))
+            },
+            Waveform::Pulse    => pulse(t, dt, width),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match *self {
+            Waveform::Saw      => "sawtooth wave",
+            Waveform::Square   => "square wave",
+            Waveform::Triangle => "triangle wave",
+            Waveform::Pulse    => "pulse wave",
+        }
+    }
+}
+
+/// a band limited oscillator. the inputs are frequency and phase reset,
+/// pulses also have a width.
+pub struct Oscillator {
+    waveform: Waveform,
+    phase: f64,
+    sample_rate: f64,
+}
+
+impl Processor for Oscillator {
+    fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
+        let width = input.get(2).cloned().unwrap_or(Signal::Sound(0.5));
+        vec![Signal::Sound(self.next(input[0], input[1], width))]
+    }
+
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            // only pulses have a width input.
+            let width = input.get(2).map_or(Signal::Sound(0.5), |width| width[frame]);
+            output[0][frame] = Signal::Sound(self.next(input[0][frame], input[1][frame], width));
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _block_size: usize) {
+        self.sample_rate = sample_rate;
+    }
+
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        let mut inputs = vec![Signal::Sound(440.0), Signal::Trigger(false)];
+        if self.waveform == Waveform::Pulse {
+            inputs.push(Signal::Sound(0.5));
+        }
+        inputs
+    }
+
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Sound(0.0)]
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        let mut ports = vec![
+            Port::new("freq", "frequency").unit("Hz").range(0.0, self.sample_rate / 2.0),
+            Port::new("reset", "restarts the wave at the start of its period")];
+        if self.waveform == Waveform::Pulse {
+            ports.push(Port::new("width", "the part of the period the pulse is high").range(0.0, 1.0));
+        }
+        ports
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("out", self.waveform.description()).range(-1.0, 1.0)]
+    }
+
+    fn type_name(&self) -> String {
+        String::from(self.waveform.name())
+    }
+}
+
+impl Oscillator {
+    pub fn new(waveform: Waveform) -> Oscillator {
+        Oscillator { waveform: waveform, phase: 0.0, sample_rate: SAMPLERATE }
+    }
+
+    fn next(&mut self, freq: Signal, reset: Signal, width: Signal) -> f64 {
+        if reset == Signal::Trigger(true) {
+            self.phase = 0.0;
+        }
+        let increment = freq.value() / self.sample_rate;
+        let t = self.phase;
+        self.phase = wrap(self.phase + increment);
+        // above half the sample rate the corrections would overlap.
+        let dt = increment.abs().min(0.5);
+        // widths of 0 and 1 would be silent, and the two corrections would meet.
+        let width = width.value().clamp(0.01, 0.99);
+        self.waveform.value(t, dt, width)
+    }
+}
@@ -0,0 +1,89 @@
+use std::error;
+use std::fmt;
+use std::io;
+use toml;
+use mesh::mesh::MeshError;
+use registry::registry::RegistryError;
+
+/// a mesh as it is written to a patch file. processors are listed in the
+/// order of their indices, which is what connections refer to.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Patch {
+    #[serde(default, rename = "processor", skip_serializing_if = "Vec::is_empty")]
+    pub processors: Vec<PatchProcessor>,
+    #[serde(default, rename = "connection", skip_serializing_if = "Vec::is_empty")]
+    pub connections: Vec<PatchConnection>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feedback: Vec<PatchConnection>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PatchProcessor {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    // what connections in the prompt refer to it by, e.g. "sine" in "sine.freq".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    // what the processor was constructed with, e.g. the channels of a Dac.
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    // the values of the input plugs, this is where constants end up.
+    #[serde(default)]
+    pub inputs: Vec<f64>,
+}
+
+/// `from` is (processor, output plug), `to` is (processor, input plug).
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PatchConnection {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+#[derive(Debug)]
+pub enum PatchError {
+    Io(io::Error),
+    Parse(String),
+    Registry(RegistryError),
+    Mesh(MeshError),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PatchError::Io(ref err)               => write!(f, "{}", err),
+            PatchError::Parse(ref err)            => write!(f, "invalid patch: {}", err),
+            PatchError::Registry(ref err)         => write!(f, "invalid patch: {}", err),
+            PatchError::Mesh(ref err)             => write!(f, "invalid patch: {}", err),
+        }
+    }
+}
+
+impl error::Error for PatchError {}
+
+impl From<io::Error> for PatchError {
+    fn from(error: io::Error) -> PatchError {
+        PatchError::Io(error)
+    }
+}
+
+impl From<RegistryError> for PatchError {
+    fn from(error: RegistryError) -> PatchError {
+        PatchError::Registry(error)
+    }
+}
+
+impl From<MeshError> for PatchError {
+    fn from(error: MeshError) -> PatchError {
+        PatchError::Mesh(error)
+    }
+}
+
+impl Patch {
+    pub fn from_toml(text: &str) -> Result<Patch, PatchError> {
+        toml::from_str(text).map_err(|err| PatchError::Parse(err.to_string()))
+    }
+
+    pub fn to_toml(&self) -> Result<String, PatchError> {
+        toml::to_string(self).map_err(|err| PatchError::Parse(err.to_string()))
+    }
+}
@@ -0,0 +1,194 @@
+use std::error;
+use std::fmt;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Weak};
+use mesh::mesh::Processor;
+use add::add::Add;
+use mult::mult::Mult;
+use dac::dac::Dac;
+use sine::sine::Sine;
+use constant::constant::{Constant, Curve};
+use oscillator::oscillator::{Oscillator, Waveform};
+use wavetable::wavetable::{Table, Wavetable};
+use noise::noise::{Noise, Color};
+use convert::convert::{Quantize, IntToSound, Rounding};
+
+/// builds a processor from the arguments it was given, e.g. in the prompt
+/// after `new <name>`. returns a message saying what was wrong with them
+/// otherwise.
+pub type Constructor = Box<Fn(&[&str]) -> Result<Box<Processor>, String>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    UnknownProcessor(String),
+    // (processor name, what was wrong)
+    InvalidArguments(String, String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RegistryError::UnknownProcessor(ref name) =>
+                write!(f, "module \"{}\" not known", name),
+            RegistryError::InvalidArguments(ref name, ref err) =>
+                write!(f, "invalid arguments for \"{}\": {}", name, err),
+        }
+    }
+}
+
+impl error::Error for RegistryError {}
+
+/// maps processor names to their constructors. names are case insensitive,
+/// so the `type_name` of a processor can be used to build it again.
+pub struct ProcessorRegistry {
+    constructors: HashMap<String, Constructor>,
+}
+
+impl ProcessorRegistry {
+    /// a registry without any processors.
+    pub fn empty() -> ProcessorRegistry {
+        ProcessorRegistry { constructors: HashMap::new() }
+    }
+
+    /// a registry with the processors of this crate.
+    pub fn new() -> ProcessorRegistry {
+        let mut registry = ProcessorRegistry::empty();
+        registry.register("constant", |arguments| {
+            let curve = match arguments.get(0) {
+                None | Some(&"linear") => Curve::Linear,
+                Some(&"exponential")   => Curve::Exponential,
+                Some(curve) => return Err(format!("\"{}\" is not a curve, use linear or exponential",
+                                                  curve)),
+            };
+            Ok(Box::new(Constant::with_curve(curve)))
+        });
+        registry.register("sine", |_| Ok(Box::new(Sine::new())));
+        for &waveform in &[Waveform::Saw, Waveform::Square, Waveform::Triangle, Waveform::Pulse] {
+            registry.register(waveform.name(), move |_| Ok(Box::new(Oscillator::new(waveform))));
+        }
+        // tables are shared by the wavetables with the same arguments, as
+        // long as any of them is left. wav files are told apart by their
+        // contents, so a file that changed since is loaded again.
+        let tables: RefCell<HashMap<Vec<String>, Weak<Table>>> = RefCell::new(HashMap::new());
+        registry.register("wavetable", move |arguments| {
+            let mut key: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+            match arguments.get(0) {
+                Some(&"harmonics") | None => (),
+                Some(path) => match fs::read(path) {
+                    Ok(contents) => {
+                        let mut hasher = DefaultHasher::new();
+                        contents.hash(&mut hasher);
+                        key.push(hasher.finish().to_string());
+                    },
+                    Err(_) => (),
+                },
+            }
+            let cached = tables.borrow().get(&key).and_then(|table| table.upgrade());
+            let table = match cached {
+                Some(table) => table,
+                None => {
+                    let table = Arc::new(try!(table_from_arguments(arguments)));
+                    let mut tables = tables.borrow_mut();
+                    tables.retain(|_, table| table.strong_count() > 0);
+                    tables.insert(key, Arc::downgrade(&table));
+                    table
+                },
+            };
+            Ok(Box::new(Wavetable::new(table)))
+        });
+        registry.register("noise", |arguments| {
+            let color = match arguments.get(0) {
+                Some(name) => try!(Color::from_name(name).ok_or_else(||
+                    format!("\"{}\" is not a color, use white, pink or brown", name))),
+                None => Color::White,
+            };
+            let seed = match arguments.get(1) {
+                Some(seed) => try!(seed.parse::<u64>()
+                    .map_err(|_| format!("\"{}\" is not a seed", seed))),
+                None => 0,
+            };
+            Ok(Box::new(Noise::with_seed(color, seed)))
+        });
+        registry.register("add", |_| Ok(Box::new(Add::new())));
+        registry.register("mult", |_| Ok(Box::new(Mult::new())));
+        registry.register("quantize", |arguments| {
+            let rounding = match arguments.get(0) {
+                Some(name) => try!(Rounding::from_name(name).ok_or_else(||
+                    format!("\"{}\" is not a rounding, use round, floor, ceil or truncate", name))),
+                None => Rounding::Round,
+            };
+            Ok(Box::new(Quantize::with_rounding(rounding)))
+        });
+        registry.register("inttosound", |_| Ok(Box::new(IntToSound::new())));
+        registry.register("dac", |arguments| {
+            let channels = match arguments.get(0) {
+                Some(channels) => try!(channels.parse::<usize>()
+                    .map_err(|_| format!("\"{}\" is not a number of channels", channels))),
+                None => 1,
+            };
+            Ok(Box::new(Dac::with_channels(channels)))
+        });
+        registry
+    }
+
+    /// adds a processor under `name`, replacing any processor of that name.
+    pub fn register<F>(&mut self, name: &str, constructor: F)
+        where F: Fn(&[&str]) -> Result<Box<Processor>, String> + 'static {
+        self.constructors.insert(name.to_lowercase(), Box::new(constructor));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(&name.to_lowercase())
+    }
+
+    /// the registered names, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.constructors.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn create(&self, name: &str, arguments: &[&str])
+        -> Result<Box<Processor>, RegistryError> {
+        match self.constructors.get(&name.to_lowercase()) {
+            Some(constructor) => constructor(arguments)
+                .map_err(|err| RegistryError::InvalidArguments(name.to_string(), err)),
+            None => Err(RegistryError::UnknownProcessor(name.to_string())),
+        }
+    }
+}
+
+// "harmonics <amplitudes> [/ <amplitudes> ...]" with a frame per group,
+// "<wav file> [frame size]", or nothing for a sine.
+fn table_from_arguments(arguments: &[&str]) -> Result<Table, String> {
+    const SIZE: usize = 2048;
+    let table = match arguments.get(0) {
+        None => Table::from_harmonics(&[vec![1.0]], SIZE),
+        Some(&"harmonics") => {
+            let mut frames = vec![Vec::new()];
+            for argument in &arguments[1..] {
+                if *argument == "/" {
+                    frames.push(Vec::new());
+                    continue;
+                }
+                let amplitude = try!(argument.parse::<f64>()
+                    .map_err(|_| format!("\"{}\" is not an amplitude", argument)));
+                frames.last_mut().unwrap().push(amplitude);
+            }
+            Table::from_harmonics(&frames, SIZE)
+        },
+        Some(path) => {
+            let size = match arguments.get(1) {
+                Some(size) => try!(size.parse::<usize>()
+                    .map_err(|_| format!("\"{}\" is not a frame size", size))),
+                None => SIZE,
+            };
+            Table::from_wav(path, size)
+        },
+    };
+    table.map_err(|err| err.to_string())
+}
@@ -0,0 +1,68 @@
+use std::cell::UnsafeCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// a fixed size single producer, single consumer queue. pushing and popping
+/// never allocate or lock, so the engine can use it from the audio thread.
+struct RingBuffer<T> {
+    slots: Vec<UnsafeCell<Option<T>>>,
+    // both only ever grow, the slot is the index modulo the capacity.
+    read: AtomicUsize,
+    write: AtomicUsize,
+}
+
+// a slot is only touched by the producer before `write` is advanced past it
+// and only by the consumer before `read` is advanced past it.
+unsafe impl<T: Send> Sync for RingBuffer<T> {}
+
+pub struct Producer<T> {
+    buffer: Arc<RingBuffer<T>>,
+}
+
+pub struct Consumer<T> {
+    buffer: Arc<RingBuffer<T>>,
+}
+
+pub fn ring_buffer<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    let mut slots = Vec::with_capacity(capacity);
+    for _ in 0..capacity {
+        slots.push(UnsafeCell::new(Option::None));
+    }
+    let buffer = Arc::new(RingBuffer {
+        slots: slots,
+        read: AtomicUsize::new(0),
+        write: AtomicUsize::new(0),
+    });
+    (Producer { buffer: buffer.clone() }, Consumer { buffer: buffer })
+}
+
+impl<T> Producer<T> {
+    /// hands the value back if the buffer is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        let buffer = &self.buffer;
+        let write = buffer.write.load(Ordering::Relaxed);
+        let read = buffer.read.load(Ordering::Acquire);
+        if write - read == buffer.slots.len() {
+            return Err(value);
+        }
+        unsafe {
+            *buffer.slots[write % buffer.slots.len()].get() = Option::Some(value);
+        }
+        buffer.write.store(write + 1, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn pop(&mut self) -> Option<T> {
+        let buffer = &self.buffer;
+        let read = buffer.read.load(Ordering::Relaxed);
+        let write = buffer.write.load(Ordering::Acquire);
+        if read == write {
+            return Option::None;
+        }
+        let value = unsafe { (*buffer.slots[read % buffer.slots.len()].get()).take() };
+        buffer.read.store(read + 1, Ordering::Release);
+        value
+    }
+}
@@ -1,40 +1,72 @@
 use mesh::mesh::Signal;
 use mesh::mesh::Processor;
+use mesh::mesh::Port;
 use mesh::mesh::SAMPLERATE;
 use std::f64::consts::PI;
 
+/// a sine wave. the phase is kept in [0, 1) periods, so it doesn't lose
+/// precision however long it runs.
 pub struct Sine {
     phase: f64,
+    sample_rate: f64,
 }
 
 impl Sine {
     pub fn new() -> Sine {
-        Sine {phase: 0.0}
+        Sine {phase: 0.0, sample_rate: SAMPLERATE}
+    }
+
+    // inputs: frequency, phase offset, sync and fm, see `input_ports`.
+    fn next(&mut self, freq: Signal, offset: Signal, sync: Signal, fm: Signal) -> f64 {
+        if sync == Signal::Trigger(true) {
+            self.phase = 0.0;
+        }
+        let output = f64::sin(2.0 * PI * (self.phase + offset.value()));
+        // a negative frequency runs the phase backwards, which is what
+        // makes the fm go through zero.
+        self.phase += (freq.value() + fm.value()) / self.sample_rate;
+        self.phase -= self.phase.floor();
+        output
     }
 }
 
 impl Processor for Sine {
     fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
-        let freq: f64;
-        let output: f64;
-        match input[0] {
-            Signal::Sound(a) => freq = a,
-            _                => panic!(),
+        vec![Signal::Sound(self.next(input[0], input[1], input[2], input[3]))]
+    }
+
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            let value = self.next(input[0][frame], input[1][frame],
+                                  input[2][frame], input[3][frame]);
+            output[0][frame] = Signal::Sound(value);
         }
-        output = f64::sin(self.phase);
-        self.phase += (freq/SAMPLERATE) * PI; 
-        
-        vec![Signal::Sound(output)]
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _block_size: usize) {
+        self.sample_rate = sample_rate;
     }
 
     fn input_types_and_defaults(&self) -> Vec<Signal> {
-        vec![Signal::Sound(440.0)]
+        vec![Signal::Sound(440.0), Signal::Sound(0.0), Signal::Trigger(false), Signal::Sound(0.0)]
     }
 
     fn output_types(&self) -> Vec<Signal> {
         vec![Signal::Sound(0.0)]
     }
 
+    fn input_ports(&self) -> Vec<Port> {
+        vec![Port::new("freq", "frequency").unit("Hz").range(0.0, self.sample_rate / 2.0),
+             Port::new("phase", "added to the phase").unit("periods").range(0.0, 1.0),
+             Port::new("sync", "restarts the period, for hard sync"),
+             Port::new("fm", "added to the frequency, may take it below zero").unit("Hz")]
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("out", "sine wave").range(-1.0, 1.0)]
+    }
+
     fn type_name(&self) -> String {
         String::from("Sine")
     }
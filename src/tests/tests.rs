@@ -1,43 +1,1009 @@
-use mesh::mesh::Mesh;
+use mesh::mesh::{Mesh, MeshConfig, MeshError, Processor, Signal};
 use sine::sine::Sine;
 use dac::dac::Dac;
 use add::add::Add;
 use mult::mult::Mult;
-use constant::constant::Constant;
+use constant::constant::{Constant, Curve};
+use backend::file::FileBackend;
+use backend::null::NullBackend;
+use alloc_tracker::alloc_tracker::allocations;
+use registry::registry::{ProcessorRegistry, RegistryError};
+use patch::patch::Patch;
+use engine::engine::EngineEvent;
+use convert::convert::{Quantize, Rounding};
+use oscillator::oscillator::{Oscillator, Waveform};
+use wavetable::wavetable::{Table, TableError, Wavetable};
+use wav::wav::{WavWriter, read_wav};
+use noise::noise::{Noise, Color};
+use command::command::{self, Command, CommandError, ScriptError};
+use std::env;
+use std::fs;
+use std::thread;
+use std::time::Duration;
 
+// opens the audio device and reads commands from the terminal, run it
+// with `cargo test -- --ignored prompt`.
 #[test]
+#[ignore]
 fn prompt() {
     let mut mesh = Mesh::new();
     let stream = mesh.run();
-    loop {
-        mesh.prompt();
+    mesh.repl();
+}
+
+#[test]
+fn render_offline() {
+    let mut mesh = Mesh::new();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //1
+    mesh.set_constant(0, 0.25).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    let samples = mesh.render(128).unwrap();
+    assert_eq!(samples.len(), 128);
+    for sample in samples {
+        assert_eq!(sample, 0.25);
+    }
+}
+
+#[test]
+fn delete_processor() {
+    let mut mesh = Mesh::new();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //1
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //2
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 0).unwrap();
+    mesh.set_constant(0, 440.0).unwrap();
+    assert!(mesh.render(64).unwrap().iter().any(|s| *s != 0.0));
+    mesh.delete_processor(1).unwrap();
+    assert_eq!(mesh.delete_processor(1), Err(MeshError::NoSuchProcessor(1)));
+    assert!(mesh.topologically_ordered.is_some());
+    for sample in mesh.render(64).unwrap() {
+        assert_eq!(sample, 0.0);
+    }
+}
+
+#[test]
+fn disconnect() {
+    let mut mesh = Mesh::new();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //1
+    mesh.set_constant(0, 0.5).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    assert_eq!(mesh.render(1).unwrap(), vec![0.5]);
+    mesh.disconnect((0, 0), (1, 0)).unwrap();
+    assert_eq!(mesh.disconnect((0, 0), (1, 0)),
+               Err(MeshError::NoSuchConnection((0, 0), (1, 0))));
+    assert_eq!(mesh.render(1).unwrap(), vec![0.0]);
+}
+
+#[test]
+fn stereo_dacs_are_summed() {
+    let mut config = MeshConfig::new();
+    config.channels = 2;
+    let mut mesh = Mesh::with_config(config).unwrap();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap();        //0
+    mesh.new_processor(Box::new(Constant::new())).unwrap();        //1
+    mesh.new_processor(Box::new(Dac::with_channels(2))).unwrap();  //2
+    mesh.new_processor(Box::new(Dac::new())).unwrap();             //3
+    mesh.set_constant(0, 0.25).unwrap();
+    mesh.set_constant(1, -0.5).unwrap();
+    mesh.new_connection(0, 0, 2, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 1).unwrap();
+    mesh.new_connection(0, 0, 3, 0).unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.5, -0.5, 0.5, -0.5]);
+}
+
+#[test]
+fn block_processing_matches_per_frame() {
+    let mut mesh = Mesh::new();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //1
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //2
+    mesh.set_constant(0, 300.0).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 0).unwrap();
+    // 200 frames span several blocks and end in a partial one.
+    let samples = mesh.render(200).unwrap();
+
+    let mut sine = Sine::new();
+    let mut inputs = sine.input_types_and_defaults();
+    inputs[0] = Signal::Sound(300.0);
+    for sample in samples {
+        match sine.process(&inputs)[0] {
+            Signal::Sound(a) => assert_eq!(sample, a as f32),
+            _                => panic!(),
+        }
+    }
+}
+
+#[test]
+fn audio_path_does_not_allocate() {
+    let mut config = MeshConfig::new();
+    config.channels = 2;
+    let mut mesh = Mesh::with_config(config).unwrap();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap();       //0
+    mesh.new_processor(Box::new(Sine::new())).unwrap();           //1
+    mesh.new_processor(Box::new(Mult::new())).unwrap();           //2
+    mesh.new_processor(Box::new(Add::new())).unwrap();            //3
+    mesh.new_processor(Box::new(Dac::with_channels(2))).unwrap(); //4
+    mesh.set_constant(0, 220.0).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 0).unwrap();
+    mesh.new_connection(1, 0, 3, 1).unwrap();
+    mesh.new_connection(2, 0, 4, 0).unwrap();
+    mesh.new_connection(3, 0, 4, 1).unwrap();
+    mesh.disconnect((1, 0), (3, 1)).unwrap();
+    mesh.delete_processor(2).unwrap();
+    mesh.set_constant(0, 330.0).unwrap();
+    let before = allocations();
+    mesh.render(1000).unwrap();
+    assert_eq!(allocations(), before);
+}
+
+#[test]
+fn feedback_connection() {
+    let mut mesh = Mesh::new();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Add::new())).unwrap();      //1
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //2
+    mesh.set_constant(0, 1.0).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 0).unwrap();
+    // a plain connection may not close a cycle.
+    assert_eq!(mesh.new_connection(1, 0, 1, 1), Err(MeshError::Cycle((1, 0), (1, 1))));
+    assert!(mesh.topologically_ordered.is_some());
+    // the add accumulates, one step per block.
+    mesh.new_feedback_connection((1, 0), (1, 1)).unwrap();
+    let samples = mesh.render(3 * 64).unwrap();
+    assert_eq!((samples[0], samples[64], samples[128]), (1.0, 2.0, 3.0));
+}
+
+#[test]
+fn sample_rate_reaches_processors() {
+    let mut config = MeshConfig::new();
+    config.sample_rate = 96000.0;
+    let mut mesh = Mesh::with_config(config).unwrap();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //1
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //2
+    mesh.set_constant(0, 300.0).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 0).unwrap();
+    let samples = mesh.render(100).unwrap();
+    assert_eq!(mesh.ports(1).unwrap().0[0].range, Some((0.0, 48000.0)));
+
+    let mut sine = Sine::new();
+    sine.prepare(96000.0, 64);
+    let mut inputs = sine.input_types_and_defaults();
+    inputs[0] = Signal::Sound(300.0);
+    for sample in samples {
+        match sine.process(&inputs)[0] {
+            Signal::Sound(a) => assert_eq!(sample, a as f32),
+            _                => panic!(),
+        }
+    }
+}
+
+#[test]
+fn editing_errors() {
+    let mut mesh = Mesh::new();
+    assert_eq!(mesh.render(1), Err(MeshError::NotRunning));
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //1
+    assert_eq!(mesh.new_connection(0, 0, 7, 0), Err(MeshError::NoSuchProcessor(7)));
+    assert_eq!(mesh.new_connection(0, 1, 1, 0), Err(MeshError::NoSuchOutput(0, 1)));
+    assert_eq!(mesh.new_connection(0, 0, 1, 3), Err(MeshError::NoSuchInput(1, 3)));
+    assert_eq!(mesh.set_constant(5, 1.0), Err(MeshError::NoSuchProcessor(5)));
+
+    let invalid = [(0.0, 1, 64), (-44100.0, 1, 64), (44100.0, 1, 0), (44100.0, 0, 64)];
+    for &(sample_rate, channels, frames_per_buffer) in &invalid {
+        let config = MeshConfig {
+            sample_rate: sample_rate,
+            channels: channels,
+            frames_per_buffer: frames_per_buffer,
+        };
+        match Mesh::with_config(config) {
+            Err(MeshError::InvalidConfig(_)) => (),
+            _                                => panic!(),
+        }
+    }
+}
+
+#[test]
+fn edit_before_running() {
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //1
+    mesh.new_processor(Box::new(Add::new())).unwrap();      //2
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //3
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //4
+    mesh.set_constant(0, 0.25).unwrap();
+    mesh.set_constant(1, 0.5).unwrap();
+    mesh.new_connection(0, 0, 2, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 1).unwrap();
+    mesh.new_connection(2, 0, 4, 0).unwrap();
+    mesh.delete_processor(3).unwrap();
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.75, 0.75]);
+}
+
+#[test]
+fn new_engines() {
+    let mut mesh = Mesh::new();
+    mesh.run_offline().unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //1
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //2
+    mesh.set_constant(0, 0.5).unwrap();
+    mesh.new_connection(0, 0, 2, 0).unwrap();
+    mesh.delete_processor(1).unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.5, 0.5]);
+    // the next engine goes on with the processors of the last one.
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.5, 0.5]);
+
+    mesh.run_with(Box::new(NullBackend::new())).unwrap();
+    assert_eq!(mesh.run_offline(), Err(MeshError::AlreadyRunning));
+    mesh.stop().unwrap();
+    // set while nothing runs.
+    mesh.set_constant(0, 0.25).unwrap();
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.25, 0.25]);
+
+    // also the ones the registry doesn't know.
+    mesh.new_processor(Box::new(Level(0.5))).unwrap();      //3
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //4
+    mesh.new_connection(3, 0, 4, 0).unwrap();
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.75, 0.75]);
+    mesh.run_with(Box::new(NullBackend::new())).unwrap();
+    mesh.stop().unwrap();
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.75, 0.75]);
+
+    // and they keep their state.
+    let sine = |mesh: &mut Mesh| {
+        mesh.new_processor(Box::new(Sine::new())).unwrap(); //0
+        mesh.new_processor(Box::new(Dac::new())).unwrap();  //1
+        mesh.new_connection(0, 0, 1, 0).unwrap();
+    };
+    let mut reference = Mesh::new();
+    sine(&mut reference);
+    reference.run_offline().unwrap();
+    let mut mesh = Mesh::new();
+    sine(&mut mesh);
+    mesh.run_offline().unwrap();
+    let mut samples = mesh.render(2).unwrap();
+    mesh.run_offline().unwrap();
+    samples.extend(mesh.render(2).unwrap());
+    assert_eq!(samples, reference.render(4).unwrap());
+}
+
+#[test]
+fn file_backend() {
+    let path = env::temp_dir().join("ripplemesh_file_backend.wav");
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //1
+    mesh.set_constant(0, 0.5).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.run_with(Box::new(FileBackend::new(path.clone()))).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    mesh.stop().unwrap();
+    let bytes = fs::read(&path).unwrap();
+    let wav = read_wav(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let data_size = u32::from_le_bytes([bytes[40], bytes[41], bytes[42], bytes[43]]);
+    assert_eq!(data_size as usize, bytes.len() - 44);
+    assert_eq!((wav.channels, wav.sample_rate), (1, 44100));
+    // whole buffers, all of them with the constant.
+    assert!(wav.samples.len() > 0);
+    assert_eq!(wav.samples.len() % 64, 0);
+    assert!(wav.samples.iter().all(|&sample| sample == 0.5));
+}
+
+#[test]
+fn save_and_load_patch() {
+    let path = env::temp_dir().join("ripplemesh_patch.toml");
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Constant::new())).unwrap();        //0
+    mesh.new_processor(Box::new(Sine::new())).unwrap();            //1
+    mesh.new_processor(Box::new(Dac::with_channels(2))).unwrap();  //2
+    mesh.set_constant(0, 441.0).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 1).unwrap();
+    // deleted processors keep their index in the patch.
+    mesh.new_processor(Box::new(Add::new())).unwrap();            //3
+    mesh.new_connection(1, 0, 3, 0).unwrap();
+    mesh.delete_processor(3).unwrap();
+    mesh.save(&path).unwrap();
+
+    let mut loaded = Mesh::new();
+    loaded.new_processor(Box::new(Add::new())).unwrap();
+    loaded.load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.processor_types, mesh.processor_types);
+    assert_eq!(loaded.list(), mesh.list());
+    assert_eq!(loaded.delete_processor(3), Err(MeshError::NoSuchProcessor(3)));
+
+    mesh.run_offline().unwrap();
+    loaded.run_offline().unwrap();
+    assert_eq!(loaded.render(256).unwrap(), mesh.render(256).unwrap());
+}
+
+// outputs a fixed level, given as construction argument.
+struct Level(f64);
+
+impl Processor for Level {
+    fn process(&mut self, _input: &Vec<Signal>) -> Vec<Signal> {
+        vec![Signal::Sound(self.0)]
+    }
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        vec![]
+    }
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Sound(0.0)]
+    }
+    fn type_name(&self) -> String {
+        String::from("Level")
+    }
+    fn arguments(&self) -> Option<Vec<String>> {
+        Option::Some(vec![self.0.to_string()])
+    }
+}
+
+#[test]
+fn registry() {
+    let mut mesh = Mesh::new();
+    assert!(mesh.registry().contains("Sine"));
+    // deleted processors are `Dummy`s, which can't be created by name.
+    assert!(!mesh.registry().contains("dummy"));
+    assert_eq!(mesh.registry().create("dac", &["two"]).err(),
+               Some(RegistryError::InvalidArguments(String::from("dac"),
+                                                    String::from("\"two\" is not a number of channels"))));
+    assert_eq!(mesh.registry().create("level", &[]).err(),
+               Some(RegistryError::UnknownProcessor(String::from("level"))));
+
+    mesh.registry_mut().register("level", |arguments| {
+        match arguments.get(0).and_then(|a| a.parse::<f64>().ok()) {
+            Some(level) => Ok(Box::new(Level(level))),
+            None        => Err(String::from("needs a level")),
+        }
+    });
+    let dac = mesh.registry().create("dac", &["2"]).unwrap();
+    assert_eq!(dac.input_types_and_defaults().len(), 2);
+
+    let patch = Patch::from_toml(r#"
+        [[processor]]
+        type = "Level"
+        arguments = ["0.25"]
+
+        [[processor]]
+        type = "Dac"
+
+        [[connection]]
+        from = [0, 0]
+        to = [1, 0]
+    "#).unwrap();
+    mesh.apply_patch(patch).unwrap();
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.25, 0.25]);
+}
+
+#[test]
+fn connect_by_name() {
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //1
+    mesh.new_processor(Box::new(Mult::new())).unwrap();     //2
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //3
+    assert_eq!(mesh.processor_name(1), Ok("constant2"));
+    assert_eq!(mesh.ports(2).unwrap().0[1].name, "b");
+    mesh.rename_processor(0, "gain").unwrap();
+    assert_eq!(mesh.rename_processor(1, "gain"), Err(MeshError::InvalidName(String::from("gain"))));
+    assert_eq!(mesh.rename_processor(1, "3"), Err(MeshError::InvalidName(String::from("3"))));
+
+    assert_eq!(mesh.find_input("mult.b"), Ok((2, 1)));
+    assert_eq!(mesh.find_input("2.1"), Ok((2, 1)));
+    assert_eq!(mesh.find_output("mult.b"), Err(MeshError::UnknownName(String::from("mult.b"))));
+    mesh.connect_by_name("gain.out", "mult.a").unwrap();
+    mesh.connect_by_name("constant2.0", "mult.b").unwrap();
+    mesh.connect_by_name("mult.product", "dac.ch0").unwrap();
+    mesh.set_constant(0, 0.5).unwrap();
+    mesh.set_constant(1, 0.5).unwrap();
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(2).unwrap(), vec![0.25, 0.25]);
+}
+
+#[test]
+fn introspection() {
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //1
+    mesh.new_processor(Box::new(Add::new())).unwrap();      //2
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //3
+    mesh.delete_processor(2).unwrap();
+    mesh.new_connection(1, 0, 3, 0).unwrap();
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.connect_feedback((1, 0), (0, 0)).unwrap();
+    mesh.set_constant_ramped(0, 2.0, 0.5).unwrap();
+
+    assert_eq!(mesh.list(), "0: constant (Constant)\n1: sine (Sine)\n3: dac (Dac)\n");
+    assert_eq!(mesh.describe_ports(1).unwrap(),
+               concat!("inputs:\n",
+                       "  0 freq: sound 440 Hz [0, 22050], frequency\n",
+                       "  1 phase: sound 0 periods [0, 1], added to the phase\n",
+                       "  2 sync: trigger false, restarts the period, for hard sync\n",
+                       "  3 fm: sound 0 Hz, added to the frequency, may take it below zero\n",
+                       "outputs:\n",
+                       "  0 out: sound [-1, 1], sine wave\n"));
+    assert_eq!(mesh.describe_ports(0).unwrap(),
+               concat!("inputs:\n",
+                       "  0 value: sound 0 (now 2), the value, set with `Mesh::set_constant`\n",
+                       "  1 ramp: sound 0 (now 0.5) s, how long it takes to reach a new value\n",
+                       "outputs:\n",
+                       "  0 out: sound, the value\n"));
+    assert_eq!(mesh.describe_ports(2), Err(MeshError::NoSuchProcessor(2)));
+    assert_eq!(mesh.edges(),
+               "constant.out (0.0) -> sine.freq (1.0)\n\
+                sine.out (1.0) -> dac.ch0 (3.0)\n\
+                sine.out (1.0) -> constant.value (0.0) (feedback)\n");
+    assert_eq!(mesh.order(), "0: constant\n1: sine\n3: dac\n");
+}
+
+#[test]
+fn command_parsing() {
+    assert_eq!(command::split(r#"save "my patch.toml" # a comment"#).unwrap(),
+               vec!["save", "my patch.toml"]);
+    assert_eq!(command::split(r#"name 0 'a "b"' c\ d """#).unwrap(),
+               vec!["name", "0", "a \"b\"", "c d", ""]);
+    assert!(match command::split("load \"x") { Err(CommandError::UnclosedQuote) => true, _ => false });
+    assert_eq!(command::parse("  # nothing").unwrap(), None);
+    assert_eq!(command::parse("connect 0 0 1 0").unwrap(),
+               Some(Command::Connect(String::from("0.0"), String::from("1.0"))));
+    assert!(match command::parse("new") { Err(CommandError::Usage(_)) => true, _ => false });
+    assert!(match command::parse("connect 1 a 2") { Err(CommandError::Usage(_)) => true, _ => false });
+    assert!(match command::parse("constant 0 loud") {
+        Err(CommandError::InvalidNumber(ref word)) => word == "loud",
+        _ => false,
+    });
+    assert!(match command::parse("conect") { Err(CommandError::UnknownCommand(_)) => true, _ => false });
+}
+
+#[test]
+fn run_commands() {
+    let mut mesh = Mesh::new();
+    assert_eq!(mesh.run_command("new constant").unwrap(), "0: constant\n");
+    assert_eq!(mesh.run_command("new dac").unwrap(), "1: dac\n");
+    assert!(mesh.run_command("new bogus").is_err());
+    assert!(mesh.run_command("connect constant.out 7.0").is_err());
+    assert!(mesh.run_command("delete 5").is_err());
+    assert!(mesh.run_command("connect 1 a").is_err());
+    mesh.run_command("connect constant.out dac.ch0").unwrap();
+    mesh.run_command("constant constant 0.5").unwrap();
+    assert_eq!(mesh.run_command("edges").unwrap(), "constant.out (0.0) -> dac.ch0 (1.0)\n");
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(1).unwrap(), vec![0.5]);
+}
+
+#[test]
+fn scripts() {
+    let script = "
+        # the mesh of `io`, with names instead of indices
+        base    = new constant
+        rate    = new constant
+        depth   = new constant
+        lfo     = new sine
+        carrier = new sine
+        scale   = new mult
+        offset  = new add
+        new dac
+        constant base 442.1
+        constant rate 1042.6
+        constant depth 888.8
+        connect base.out offset.a
+        connect rate.out scale.a
+        connect depth.out lfo.freq
+        connect lfo.out scale.b
+        connect scale.product offset.b
+        connect offset.sum carrier.freq
+        connect carrier.out dac.ch0
+    ";
+    let mut scripted = Mesh::new();
+    scripted.run_script(script).unwrap();
+
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //1
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //2
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //3
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //4
+    mesh.new_processor(Box::new(Mult::new())).unwrap();     //5
+    mesh.new_processor(Box::new(Add::new())).unwrap();      //6
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //7
+    mesh.set_constant(0, 442.1).unwrap();
+    mesh.set_constant(1, 1042.6).unwrap();
+    mesh.set_constant(2, 888.8).unwrap();
+    mesh.new_connection(0, 0, 6, 0).unwrap();
+    mesh.new_connection(1, 0, 5, 0).unwrap();
+    mesh.new_connection(2, 0, 3, 0).unwrap();
+    mesh.new_connection(3, 0, 5, 1).unwrap();
+    mesh.new_connection(5, 0, 6, 1).unwrap();
+    mesh.new_connection(6, 0, 4, 0).unwrap();
+    mesh.new_connection(4, 0, 7, 0).unwrap();
+
+    scripted.run_offline().unwrap();
+    mesh.run_offline().unwrap();
+    assert_eq!(scripted.render(256).unwrap(), mesh.render(256).unwrap());
+
+    let mut failing = Mesh::new();
+    let err = failing.run_script("osc = new sine\nosc = new sine\nnew dac").unwrap_err();
+    assert!(match err {
+        ScriptError::Line(2, CommandError::Mesh(MeshError::InvalidName(_))) => true,
+        _ => false,
+    });
+    assert_eq!(failing.list(), "0: osc (Sine)\n");
+
+    // scripts that run each other would never end.
+    let first = env::temp_dir().join("ripplemesh_first_script.txt");
+    let second = env::temp_dir().join("ripplemesh_second_script.txt");
+    fs::write(&first, format!("new sine\nrun \"{}\"\n", second.display())).unwrap();
+    fs::write(&second, format!("new dac\nrun \"{}\"\n", first.display())).unwrap();
+    let canonical = fs::canonicalize(&first).unwrap();
+    let mut recursive = Mesh::new();
+    let err = recursive.run_script_file(&first).unwrap_err();
+    fs::remove_file(&first).unwrap();
+    fs::remove_file(&second).unwrap();
+    let inner = match err {
+        ScriptError::Line(2, CommandError::Script(inner)) => inner,
+        _ => panic!(),
+    };
+    assert!(match *inner {
+        ScriptError::Line(2, CommandError::Script(ref err)) => match **err {
+            ScriptError::Recursion(ref path) => *path == canonical,
+            _ => false,
+        },
+        _ => false,
+    });
+    assert_eq!(recursive.list(), "0: sine (Sine)\n1: dac (Dac)\n");
+}
+
+#[test]
+fn constant_ramps() {
+    let config = MeshConfig { sample_rate: 1000.0, channels: 2, ..MeshConfig::new() };
+    let mut mesh = Mesh::with_config(config).unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap();                            //0
+    mesh.new_processor(Box::new(Constant::with_curve(Curve::Exponential))).unwrap();   //1
+    mesh.new_processor(Box::new(Dac::with_channels(2))).unwrap();                      //2
+    mesh.new_connection(0, 0, 2, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 1).unwrap();
+    mesh.set_constant(0, 1.0).unwrap();
+    mesh.set_constant(1, 100.0).unwrap();
+    mesh.run_offline().unwrap();
+    // the first value is taken right away.
+    assert_eq!(&mesh.render(1).unwrap()[..], &[1.0, 100.0]);
+
+    mesh.set_constant_ramped(0, 2.0, 0.004).unwrap();
+    mesh.set_constant_ramped(1, 1600.0, 0.004).unwrap();
+    let samples = mesh.render(6).unwrap();
+    let linear: Vec<f32> = samples.iter().step_by(2).cloned().collect();
+    let exponential: Vec<f32> = samples.iter().skip(1).step_by(2).cloned().collect();
+    assert_eq!(linear, vec![1.0, 1.25, 1.5, 1.75, 2.0, 2.0]);
+    let expected = [100.0, 200.0, 400.0, 800.0, 1600.0, 1600.0];
+    for (sample, expected) in exponential.iter().zip(expected.iter()) {
+        assert!((sample - expected).abs() < 0.01);
     }
+
+    // a ramp is only for the change it was given with.
+    mesh.set_constant(0, 1.0).unwrap();
+    let samples = mesh.render(2).unwrap();
+    assert_eq!(samples[0], 1.0);
+    assert_eq!(samples[2], 1.0);
+}
+
+#[test]
+fn engine_events() {
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //1
+    assert_eq!(mesh.set_constant(1, 0.5), Err(MeshError::NotAConstant(1)));
+    assert_eq!(mesh.poll_events(), vec![]);
+
+    mesh.run_offline().unwrap();
+    mesh.set_constant(0, 0.5).unwrap();
+    mesh.render(1).unwrap();
+    assert_eq!(mesh.poll_events(),
+               vec![EngineEvent::GraphSwapped(2), EngineEvent::ConstantSet(0, 0.5)]);
+    assert_eq!(mesh.poll_events(), vec![]);
 }
 
+// sends a trigger every `period` frames.
+struct Clock {
+    period: usize,
+    frame: usize,
+}
+
+impl Processor for Clock {
+    fn process(&mut self, _input: &Vec<Signal>) -> Vec<Signal> {
+        let trigger = self.frame % self.period == 0;
+        self.frame += 1;
+        vec![Signal::Trigger(trigger)]
+    }
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        vec![]
+    }
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Trigger(false)]
+    }
+    fn type_name(&self) -> String {
+        String::from("Clock")
+    }
+}
+
+// outputs 1 on the frame it was triggered, falling by `decay` every frame.
+struct Decay {
+    decay: f64,
+    level: f64,
+}
+
+impl Processor for Decay {
+    fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
+        match input[0] {
+            Signal::Trigger(true) => self.level = 1.0,
+            _                     => self.level = (self.level - self.decay).max(0.0),
+        }
+        vec![Signal::Sound(self.level)]
+    }
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        vec![Signal::Trigger(false)]
+    }
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Sound(0.0)]
+    }
+    fn type_name(&self) -> String {
+        String::from("Decay")
+    }
+}
+
+#[test]
+fn triggers() {
+    let mut mesh = Mesh::new();
+    mesh.new_processor(Box::new(Clock { period: 3, frame: 0 })).unwrap();    //0
+    mesh.new_processor(Box::new(Decay { decay: 0.25, level: 0.0 })).unwrap(); //1
+    mesh.new_processor(Box::new(Dac::new())).unwrap();                      //2
+    assert_eq!(mesh.new_connection(0, 0, 2, 0), Err(MeshError::TypeMismatch((0, 0), (2, 0))));
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+    mesh.new_connection(1, 0, 2, 0).unwrap();
+    mesh.run_offline().unwrap();
+    // the trigger on frame 66 lands in the second block.
+    let samples = mesh.render(68).unwrap();
+    assert_eq!(&samples[..4], &[1.0, 0.75, 0.5, 1.0]);
+    assert_eq!(&samples[64..], &[0.75, 0.5, 1.0, 0.75]);
+    assert!(Signal::Trigger(true).same_type(&Signal::Trigger(false)));
+    assert!(!Signal::Trigger(true).same_type(&Signal::Gate(true)));
+}
+
+#[test]
+fn converters() {
+    let mut mesh = Mesh::with_config(MeshConfig { channels: 2, ..MeshConfig::new() }).unwrap();
+    mesh.new_processor(Box::new(Constant::new())).unwrap();                           //0
+    mesh.new_processor(Box::new(Quantize::with_rounding(Rounding::Floor))).unwrap();  //1
+    mesh.new_processor(Box::new(Dac::with_channels(2))).unwrap();                     //2
+    mesh.set_constant(0, 2.6).unwrap();
+    assert_eq!(mesh.new_converting_connection((1, 0), (2, 0)),
+               Err(MeshError::TypeMismatch((1, 0), (2, 0))));
+    mesh.new_connection(0, 0, 1, 0).unwrap();
+
+    mesh.set_auto_convert(true);
+    assert_eq!(mesh.new_converting_connection((1, 0), (2, 0)), Ok(Some(3)));
+    assert_eq!(mesh.processor_types[3].2, "IntToSound");
+    assert_eq!(mesh.new_converting_connection((0, 0), (2, 1)), Ok(None));
+    // a converter would close a cycle here.
+    assert_eq!(mesh.new_converting_connection((3, 0), (1, 0)), Err(MeshError::Cycle((3, 0), (1, 0))));
+    mesh.run_offline().unwrap();
+    assert_eq!(mesh.render(1).unwrap(), vec![2.0, 2.6]);
+
+    let mut prompt = Mesh::new();
+    prompt.run_script("new constant\nnew quantize\nnew dac\nconnect 0.0 1.0\nconvert on").unwrap();
+    assert_eq!(prompt.run_command("connect quantize.out dac.ch0").unwrap(),
+               "inserted 3: inttosound (IntToSound)\n");
+}
+
+// renders `frames` frames of an oscillator with frequency and reset inputs.
+fn oscillate(oscillator: &mut Processor, freq: f64, frames: usize) -> Vec<f64> {
+    let mut inputs = oscillator.input_types_and_defaults();
+    inputs[0] = Signal::Sound(freq);
+    (0..frames).map(|_| oscillator.process(&inputs)[0].value()).collect()
+}
+
+// the energy of `samples` at multiples of 50 Hz that are no harmonics of
+// `freq`, which is where the aliases of a 2950 Hz wave at 44.1 kHz end up.
+fn aliasing(samples: &[f64], freq: f64) -> f64 {
+    use std::f64::consts::PI;
+    let resolution = 44100.0 / samples.len() as f64;
+    let mut energy = 0.0;
+    let mut bin_freq = 50.0;
+    while bin_freq < 22050.0 {
+        if (bin_freq / freq).fract() != 0.0 {
+            let bin = bin_freq / resolution;
+            let (mut re, mut im) = (0.0, 0.0);
+            for (frame, sample) in samples.iter().enumerate() {
+                let angle = 2.0 * PI * bin * frame as f64 / samples.len() as f64;
+                re += sample * angle.cos();
+                im -= sample * angle.sin();
+            }
+            energy += re * re + im * im;
+        }
+        bin_freq += 50.0;
+    }
+    energy
+}
+
+#[test]
+fn band_limited_oscillators() {
+    let freq = 2950.0;
+    let cases: Vec<(Box<Processor>, Box<Fn(f64) -> f64>)> = vec![
+        (Box::new(Oscillator::new(Waveform::Saw)), Box::new(|t| 2.0 * t - 1.0)),
+        (Box::new(Oscillator::new(Waveform::Square)), Box::new(|t| if t < 0.5 { 1.0 } else { -1.0 })),
+        (Box::new(Oscillator::new(Waveform::Triangle)), Box::new(|t: f64| 1.0 - 4.0 * (t - 0.5).abs())),
+    ];
+    for (mut oscillator, naive) in cases {
+        let samples = oscillate(&mut *oscillator, freq, 4410);
+        let naive: Vec<f64> = (0..4410)
+            .map(|frame| naive((frame as f64 * freq / 44100.0).fract()))
+            .collect();
+        let (aliased, naive_aliased) = (aliasing(&samples, freq), aliasing(&naive, freq));
+        assert!(aliased < naive_aliased / 10.0,
+                "{}: {} is not much less than {}", oscillator.type_name(), aliased, naive_aliased);
+    }
+
+    // a pulse of width 0.5 is a square.
+    let square = oscillate(&mut Oscillator::new(Waveform::Square), freq, 100);
+    let pulse = oscillate(&mut Oscillator::new(Waveform::Pulse), freq, 100);
+    assert_eq!(square, pulse);
+}
+
+#[test]
+fn oscillator_reset() {
+    let mut mesh = Mesh::new();
+    mesh.run_command("new saw").unwrap();
+    mesh.new_processor(Box::new(Clock { period: 5, frame: 0 })).unwrap();
+    mesh.run_command("connect clock.0 saw.reset").unwrap();
+    mesh.run_command("new dac").unwrap();
+    mesh.run_command("connect saw.out dac.ch0").unwrap();
+    mesh.run_offline().unwrap();
+    let samples = mesh.render(10).unwrap();
+    assert_eq!(&samples[..5], &samples[5..]);
+    // the jump is smoothed, so a period starts halfway up it.
+    assert_eq!(samples[0], 0.0);
+}
+
+// the frequency of `samples` from the number of rising zero crossings.
+fn measure_frequency(samples: &[f32], sample_rate: f64) -> f64 {
+    let crossings = samples.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count();
+    crossings as f64 * sample_rate / samples.len() as f64
+}
+
+#[test]
+fn sine_frequency() {
+    for &sample_rate in &[44100.0, 96000.0] {
+        let config = MeshConfig { sample_rate: sample_rate, ..MeshConfig::new() };
+        let mut mesh = Mesh::with_config(config).unwrap();
+        mesh.run_script("new constant\nnew sine\nnew dac\n\
+                         connect constant.out sine.freq\nconnect sine.out dac.ch0\n\
+                         constant 0 441").unwrap();
+        mesh.run_offline().unwrap();
+        // a second, after ten seconds of phase.
+        mesh.render(sample_rate as usize * 10).unwrap();
+        let samples = mesh.render(sample_rate as usize).unwrap();
+        assert!((measure_frequency(&samples, sample_rate) - 441.0).abs() <= 1.0);
+    }
+}
+
+#[test]
+fn sine_modulation() {
+    let render = |sine: &mut Sine, inputs: Vec<Signal>, frames: usize| -> Vec<f32> {
+        (0..frames).map(|_| sine.process(&inputs)[0].value() as f32).collect()
+    };
+    let plain = vec![Signal::Sound(441.0), Signal::Sound(0.0), Signal::Trigger(false), Signal::Sound(0.0)];
+
+    // a quarter period ahead starts at the top.
+    let mut offset = plain.clone();
+    offset[1] = Signal::Sound(0.25);
+    assert!((render(&mut Sine::new(), offset, 1)[0] - 1.0).abs() < 1e-6);
+
+    // through zero fm: 0 Hz plus -441 Hz of fm runs at 441 Hz, backwards.
+    let mut through_zero = plain.clone();
+    through_zero[0] = Signal::Sound(0.0);
+    through_zero[3] = Signal::Sound(-441.0);
+    let samples = render(&mut Sine::new(), through_zero, 44100);
+    assert!(samples[1] < 0.0);
+    assert!((measure_frequency(&samples, 44100.0) - 441.0).abs() <= 1.0);
+
+    // hard sync to 100 Hz repeats the start of the 441 Hz period.
+    let mut sine = Sine::new();
+    let mut samples = Vec::new();
+    for frame in 0..882 {
+        let mut inputs = plain.clone();
+        inputs[2] = Signal::Trigger(frame % 441 == 0);
+        samples.push(sine.process(&inputs)[0].value());
+    }
+    assert_eq!(&samples[..441], &samples[441..]);
+}
+
+#[test]
+fn wavetables() {
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+    let inputs = |freq: f64, position: f64|
+        vec![Signal::Sound(freq), Signal::Trigger(false), Signal::Sound(position)];
+
+    // the first frame is a sine, the second its octave.
+    let table = Arc::new(Table::from_harmonics(&[vec![1.0], vec![0.0, 1.0]], 256).unwrap());
+    let mut first = Wavetable::new(table.clone());
+    let mut morphed = Wavetable::new(table.clone());
+    assert_eq!(Arc::strong_count(&table), 3);
+    for frame in 0..100 {
+        let t = frame as f64 * 441.0 / 44100.0;
+        let sine = (2.0 * PI * t).sin();
+        let octave = (4.0 * PI * t).sin();
+        assert!((first.process(&inputs(441.0, 0.0))[0].value() - sine).abs() < 1e-3);
+        let value = morphed.process(&inputs(441.0, 0.5))[0].value();
+        assert!((value - (sine + octave) / 2.0).abs() < 1e-3);
+    }
+
+    // a saw with every harmonic the table can hold loses the ones that
+    // would alias.
+    let saw: Vec<f64> = (1..128).map(|k| 1.0 / k as f64).collect();
+    let saw = Arc::new(Table::from_harmonics(&[saw], 256).unwrap());
+    let samples = oscillate(&mut Wavetable::new(saw), 2950.0, 4410);
+    let naive: Vec<f64> = (0..4410)
+        .map(|frame| 1.0 - 2.0 * (frame as f64 * 2950.0 / 44100.0).fract())
+        .map(|saw| saw * PI / 2.0)
+        .collect();
+    assert!(aliasing(&samples, 2950.0) < aliasing(&naive, 2950.0) / 100.0);
+
+    // frames read from a wav file.
+    let path = env::temp_dir().join("ripplemesh_wavetable.wav");
+    let cycles: Vec<Vec<f64>> = (0..2).map(|frame| {
+        (0..64).map(|n| ((frame + 1) as f64 * 2.0 * PI * n as f64 / 64.0).sin()).collect()
+    }).collect();
+    let mut writer = WavWriter::create(&path, 1, 44100).unwrap();
+    for cycle in &cycles {
+        let samples: Vec<f32> = cycle.iter().map(|&sample| sample as f32).collect();
+        writer.write(&samples).unwrap();
+    }
+    writer.finish().unwrap();
+    let loaded = Table::from_wav(&path, 64).unwrap();
+    assert_eq!(loaded.frames(), 2);
+
+    // the registry loads a file again once it changed, even while the old
+    // table is still in use and the file is as long as before.
+    let registry = ProcessorRegistry::new();
+    let arguments = [path.to_str().unwrap(), "64"];
+    let mut before = registry.create("wavetable", &arguments).unwrap();
+    let mut writer = WavWriter::create(&path, 1, 44100).unwrap();
+    writer.write(&vec![0.0; 128]).unwrap();
+    writer.finish().unwrap();
+    let mut after = registry.create("wavetable", &arguments).unwrap();
+    fs::remove_file(&path).unwrap();
+    let mut frequency = before.input_types_and_defaults();
+    frequency[0] = Signal::Sound(300.0);
+    for _ in 0..2 {
+        assert_eq!(after.process(&frequency)[0].value(), 0.0);
+    }
+    before.process(&frequency);
+    assert!(before.process(&frequency)[0].value() != 0.0);
+    let mut from_wav = Wavetable::new(Arc::new(loaded));
+    let mut from_frames = Wavetable::new(Arc::new(Table::from_frames(&cycles).unwrap()));
+    for _ in 0..100 {
+        let a = from_wav.process(&inputs(300.0, 1.0))[0].value();
+        let b = from_frames.process(&inputs(300.0, 1.0))[0].value();
+        assert!((a - b).abs() < 1e-6);
+    }
+    assert!(match Table::from_frames(&[vec![0.0; 8], vec![0.0; 6]]) {
+        Err(TableError::FrameSize(8, 6)) => true,
+        _ => false,
+    });
+
+    let mut mesh = Mesh::new();
+    mesh.run_command("new wavetable harmonics 1 / 0 1").unwrap();
+    assert_eq!(mesh.to_patch().unwrap().processors[0].arguments, vec!["harmonics", "1", "/", "0", "1"]);
+
+    // a table from frames can't be built again, the next engine keeps
+    // playing the same one, and it can't be saved.
+    let square: Vec<f64> = (0..64).map(|n| if n < 32 { 1.0 } else { -1.0 }).collect();
+    let square = Arc::new(Table::from_frames(&[square]).unwrap());
+    let build = |mesh: &mut Mesh| {
+        mesh.new_processor(Box::new(Wavetable::new(square.clone()))).unwrap(); //0
+        mesh.new_processor(Box::new(Dac::new())).unwrap();                     //1
+        mesh.new_connection(0, 0, 1, 0).unwrap();
+    };
+    let mut reference = Mesh::new();
+    build(&mut reference);
+    reference.run_offline().unwrap();
+    let mut mesh = Mesh::new();
+    build(&mut mesh);
+    mesh.run_offline().unwrap();
+    let mut samples = mesh.render(100).unwrap();
+    mesh.run_offline().unwrap();
+    samples.extend(mesh.render(100).unwrap());
+    assert_eq!(samples, reference.render(200).unwrap());
+    assert_eq!(mesh.to_patch().err(), Some(MeshError::NoArguments(0)));
+}
+
+#[test]
+fn noise() {
+    let render = |noise: &mut Noise, frames: usize| -> Vec<f64> {
+        (0..frames).map(|_| noise.process(&vec![Signal::Trigger(false)])[0].value()).collect()
+    };
+    // how much the noise changes from frame to frame compared to how loud
+    // it is, about 2 for white noise and less the darker it is.
+    let roughness = |samples: &[f64]| -> f64 {
+        let power: f64 = samples.iter().map(|s| s * s).sum();
+        let change: f64 = samples.windows(2).map(|pair| (pair[1] - pair[0]).powi(2)).sum();
+        change / power
+    };
+
+    let white = render(&mut Noise::with_seed(Color::White, 7), 44100);
+    assert_eq!(white, render(&mut Noise::with_seed(Color::White, 7), 44100));
+    assert!(white != render(&mut Noise::with_seed(Color::White, 8), 44100));
+    assert!(white.iter().all(|&sample| sample >= -1.0 && sample < 1.0));
+    assert!((white.iter().sum::<f64>() / white.len() as f64).abs() < 0.01);
+    let pink = render(&mut Noise::with_seed(Color::Pink, 7), 44100);
+    let brown = render(&mut Noise::with_seed(Color::Brown, 7), 44100);
+    assert!((roughness(&white) - 2.0).abs() < 0.1);
+    assert!(roughness(&pink) < 1.0);
+    assert!(roughness(&brown) < roughness(&pink) / 5.0);
+
+    // a reset starts the sequence over, in a mesh as well.
+    let mut mesh = Mesh::new();
+    mesh.run_command("new noise pink 7").unwrap();
+    mesh.new_processor(Box::new(Clock { period: 50, frame: 0 })).unwrap();
+    mesh.run_command("connect clock.0 noise.reset").unwrap();
+    mesh.run_command("new dac").unwrap();
+    mesh.run_command("connect noise.out dac.ch0").unwrap();
+    mesh.run_offline().unwrap();
+    let samples = mesh.render(100).unwrap();
+    assert_eq!(&samples[..50], &samples[50..]);
+    let expected: Vec<f32> = pink[..50].iter().map(|&sample| sample as f32).collect();
+    assert_eq!(&samples[..50], &expected[..]);
+}
 
 //#[test]
 fn io() {
     let mut mesh = Mesh::new();
     let stream = mesh.run();
     let mut sum = 0;
-    mesh.new_processor(Box::new(Constant::new())); //0
-    mesh.new_processor(Box::new(Constant::new())); //1
-    mesh.new_processor(Box::new(Constant::new())); //2
-    mesh.new_processor(Box::new(Sine::new()));     //3
-    mesh.new_processor(Box::new(Sine::new()));     //4
-    mesh.new_processor(Box::new(Mult::new()));     //5
-    mesh.new_processor(Box::new(Add::new()));      //6 
-    mesh.new_processor(Box::new(Dac::new()));      //7
-    mesh.set_constant(0, 442.1);
-    mesh.set_constant(1, 1042.6);
-    mesh.set_constant(2, 888.8);
-    mesh.new_connection(0, 0, 6, 0);
-    mesh.new_connection(1, 0, 5, 0);
-    mesh.new_connection(2, 0, 3, 0);
-    mesh.new_connection(3, 0, 5, 1);
-    mesh.new_connection(5, 0, 6, 1);
-    mesh.new_connection(6, 0, 4, 0);
-    mesh.new_connection(4, 0, 7, 0);
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //0
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //1
+    mesh.new_processor(Box::new(Constant::new())).unwrap(); //2
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //3
+    mesh.new_processor(Box::new(Sine::new())).unwrap();     //4
+    mesh.new_processor(Box::new(Mult::new())).unwrap();     //5
+    mesh.new_processor(Box::new(Add::new())).unwrap();      //6 
+    mesh.new_processor(Box::new(Dac::new())).unwrap();      //7
+    mesh.set_constant(0, 442.1).unwrap();
+    mesh.set_constant(1, 1042.6).unwrap();
+    mesh.set_constant(2, 888.8).unwrap();
+    mesh.new_connection(0, 0, 6, 0).unwrap();
+    mesh.new_connection(1, 0, 5, 0).unwrap();
+    mesh.new_connection(2, 0, 3, 0).unwrap();
+    mesh.new_connection(3, 0, 5, 1).unwrap();
+    mesh.new_connection(5, 0, 6, 1).unwrap();
+    mesh.new_connection(6, 0, 4, 0).unwrap();
+    mesh.new_connection(4, 0, 7, 0).unwrap();
 
     loop {}
 }
@@ -0,0 +1,172 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const HEADER_SIZE: u32 = 44;
+const FORMAT_PCM: u16 = 1;
+const FORMAT_IEEE_FLOAT: u16 = 3;
+// the actual format is the first two bytes of the subformat guid.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+// the riff chunk size, which includes the rest of the header, has to fit
+// into 32 bits.
+const MAX_DATA_SIZE: u32 = u32::MAX - (HEADER_SIZE - 8);
+
+/// writes interleaved 32 bit float samples to a wav file. the sizes in the
+/// header are patched by `finish`, or when the writer is dropped.
+pub struct WavWriter {
+    file: BufWriter<File>,
+    channels: u16,
+    sample_rate: u32,
+    samples_written: u32,
+    finished: bool,
+}
+
+impl WavWriter {
+
+    pub fn create<P: AsRef<Path>>(path: P, channels: u16, sample_rate: u32)
+                                  -> io::Result<WavWriter> {
+        let file = try!(File::create(path));
+        let mut writer = WavWriter {
+            file: BufWriter::new(file),
+            channels: channels,
+            sample_rate: sample_rate,
+            samples_written: 0,
+            finished: false,
+        };
+        try!(writer.write_header());
+        Ok(writer)
+    }
+
+    /// fails without writing anything once the file would be bigger than
+    /// the 4 GiB the sizes in the header can describe.
+    pub fn write(&mut self, samples: &[f32]) -> io::Result<()> {
+        let total = (self.samples_written as u64).checked_add(samples.len() as u64);
+        match total.and_then(|total| total.checked_mul(4)) {
+            Some(size) if size <= MAX_DATA_SIZE as u64 => (),
+            _ => return Err(io::Error::new(io::ErrorKind::Other,
+                                           "the wav file has reached its maximum size")),
+        }
+        for sample in samples {
+            try!(self.file.write_all(&sample.to_le_bytes()));
+        }
+        self.samples_written += samples.len() as u32;
+        Ok(())
+    }
+
+    /// patches the chunk sizes in the header and flushes the file.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.finished = true;
+        self.update_header()
+    }
+
+    fn update_header(&mut self) -> io::Result<()> {
+        try!(self.file.seek(SeekFrom::Start(0)));
+        try!(self.write_header());
+        try!(self.file.seek(SeekFrom::End(0)));
+        self.file.flush()
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let data_size = self.samples_written * 4;
+        let block_align = self.channels * 4;
+        let byte_rate = self.sample_rate * block_align as u32;
+        let file = &mut self.file;
+        try!(file.write_all(b"RIFF"));
+        try!(file.write_all(&(HEADER_SIZE - 8 + data_size).to_le_bytes()));
+        try!(file.write_all(b"WAVE"));
+        try!(file.write_all(b"fmt "));
+        try!(file.write_all(&16u32.to_le_bytes()));
+        try!(file.write_all(&FORMAT_IEEE_FLOAT.to_le_bytes()));
+        try!(file.write_all(&self.channels.to_le_bytes()));
+        try!(file.write_all(&self.sample_rate.to_le_bytes()));
+        try!(file.write_all(&byte_rate.to_le_bytes()));
+        try!(file.write_all(&block_align.to_le_bytes()));
+        try!(file.write_all(&32u16.to_le_bytes()));
+        try!(file.write_all(b"data"));
+        file.write_all(&data_size.to_le_bytes())
+    }
+}
+
+impl Drop for WavWriter {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.update_header().ok();
+        }
+    }
+}
+
+/// the contents of a wav file, with samples interleaved and scaled to
+/// [-1, 1].
+pub struct WavData {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// reads 8, 16, 24 and 32 bit integer and 32 bit float wav files.
+pub fn read_wav<P: AsRef<Path>>(path: P) -> io::Result<WavData> {
+    let bytes = try!(fs::read(path));
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(invalid("not a wav file"));
+    }
+    // (format, channels, sample rate, bits per sample)
+    let mut format: Option<(u16, u16, u32, u16)> = Option::None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let id = &bytes[at..at + 4];
+        let size = u32_at(&bytes, at + 4) as usize;
+        let body = &bytes[at + 8..bytes.len().min(at + 8 + size)];
+        if id == b"fmt " {
+            if body.len() < 16 {
+                return Err(invalid("the fmt chunk is too short"));
+            }
+            let mut tag = u16_at(body, 0);
+            if tag == FORMAT_EXTENSIBLE && body.len() >= 26 {
+                tag = u16_at(body, 24);
+            }
+            format = Option::Some((tag, u16_at(body, 2), u32_at(body, 4), u16_at(body, 14)));
+        } else if id == b"data" {
+            let (tag, channels, sample_rate, bits) = try!(format.ok_or_else(||
+                invalid("the data chunk comes before the fmt chunk")));
+            let samples = try!(decode(body, tag, bits));
+            return Ok(WavData { channels: channels, sample_rate: sample_rate, samples: samples });
+        }
+        // chunks are padded to an even size.
+        at += 8 + size + size % 2;
+    }
+    Err(invalid("there is no data chunk"))
+}
+
+fn decode(data: &[u8], tag: u16, bits: u16) -> io::Result<Vec<f32>> {
+    let samples = match (tag, bits) {
+        (FORMAT_PCM, 8) => data.iter()
+            .map(|&byte| (byte as f32 - 128.0) / 128.0).collect(),
+        (FORMAT_PCM, 16) => data.chunks(2).filter(|c| c.len() == 2)
+            .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0).collect(),
+        (FORMAT_PCM, 24) => data.chunks(3).filter(|c| c.len() == 3)
+            .map(|c| (i32::from_le_bytes([0, c[0], c[1], c[2]]) >> 8) as f32 / 8388608.0)
+            .collect(),
+        (FORMAT_PCM, 32) => data.chunks(4).filter(|c| c.len() == 4)
+            .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f32 / 2147483648.0)
+            .collect(),
+        (FORMAT_IEEE_FLOAT, 32) => data.chunks(4).filter(|c| c.len() == 4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect(),
+        _ => return Err(invalid("only 8, 16, 24 and 32 bit integer and 32 bit float \
+                                 samples are supported")),
+    };
+    Ok(samples)
+}
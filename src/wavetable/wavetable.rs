@@ -0,0 +1,257 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::f64::consts::PI;
+use mesh::mesh::Signal;
+use mesh::mesh::Processor;
+use mesh::mesh::Port;
+use mesh::mesh::SAMPLERATE;
+use wav::wav::read_wav;
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(io::Error),
+    // there is not a single frame.
+    Empty,
+    // (the size of the first frame, the size of another one)
+    FrameSize(usize, usize),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TableError::Io(ref err) => write!(f, "{}", err),
+            TableError::Empty       => write!(f, "the table has no frames of at least 4 samples"),
+            TableError::FrameSize(first, other) =>
+                write!(f, "all frames need {} samples like the first, not {}", first, other),
+        }
+    }
+}
+
+impl error::Error for TableError {}
+
+impl From<io::Error> for TableError {
+    fn from(error: io::Error) -> TableError {
+        TableError::Io(error)
+    }
+}
+
+/// single cycle waveforms ("frames") to morph between. every frame is kept
+/// at several levels of band limiting, one per octave, so that high notes
+/// don't alias. tables don't change once they are built, `Wavetable`s
+/// share them through an `Arc`.
+pub struct Table {
+    size: usize,
+    // [frame][level][sample], level l keeps the harmonics below size / 2 >> l.
+    frames: Vec<Vec<Vec<f64>>>,
+    // how the registry can build the table again, see `Processor::arguments`.
+    // tables made from frames in memory can't be.
+    arguments: Option<Vec<String>>,
+}
+
+impl Table {
+    /// builds a table from single cycles that all have the same number of
+    /// samples, 2048 is common. wavetables with such a table can't be saved
+    /// in a patch.
+    pub fn from_frames(cycles: &[Vec<f64>]) -> Result<Table, TableError> {
+        let size = match cycles.first() {
+            Some(cycle) if cycle.len() >= 4 => cycle.len(),
+            _                               => return Err(TableError::Empty),
+        };
+        match cycles.iter().find(|cycle| cycle.len() != size) {
+            Some(cycle) => return Err(TableError::FrameSize(size, cycle.len())),
+            None        => (),
+        }
+        let cosines: Vec<f64> = (0..size).map(|n| (2.0 * PI * n as f64 / size as f64).cos()).collect();
+        let sines: Vec<f64> = (0..size).map(|n| (2.0 * PI * n as f64 / size as f64).sin()).collect();
+        let frames = cycles.iter().map(|cycle| mipmaps(cycle, &cosines, &sines)).collect();
+        Ok(Table { size: size, frames: frames, arguments: Option::None })
+    }
+
+    /// builds a table from the amplitudes of the harmonics of each frame,
+    /// starting with the fundamental. all harmonics are sines.
+    pub fn from_harmonics(harmonics: &[Vec<f64>], size: usize) -> Result<Table, TableError> {
+        let cycles: Vec<Vec<f64>> = harmonics.iter().map(|amplitudes| {
+            (0..size).map(|n| {
+                amplitudes.iter().enumerate()
+                    .map(|(k, amplitude)| {
+                        amplitude * (2.0 * PI * ((k + 1) * n) as f64 / size as f64).sin()
+                    })
+                    .sum()
+            }).collect()
+        }).collect();
+        let mut table = try!(Table::from_frames(&cycles));
+        let mut arguments = vec![String::from("harmonics")];
+        for (frame, amplitudes) in harmonics.iter().enumerate() {
+            if frame > 0 {
+                arguments.push(String::from("/"));
+            }
+            arguments.extend(amplitudes.iter().map(|amplitude| amplitude.to_string()));
+        }
+        table.arguments = Option::Some(arguments);
+        Ok(table)
+    }
+
+    /// cuts the first channel of a wav file into frames of `frame_size`
+    /// samples. samples after the last whole frame are left out.
+    pub fn from_wav<P: AsRef<Path>>(path: P, frame_size: usize) -> Result<Table, TableError> {
+        let wav = try!(read_wav(path.as_ref()));
+        let channel: Vec<f64> = wav.samples.iter()
+            .step_by(wav.channels.max(1) as usize)
+            .map(|&sample| sample as f64)
+            .collect();
+        if frame_size == 0 {
+            return Err(TableError::Empty);
+        }
+        let cycles: Vec<Vec<f64>> = channel.chunks(frame_size)
+            .filter(|cycle| cycle.len() == frame_size)
+            .map(|cycle| cycle.to_vec())
+            .collect();
+        let mut table = try!(Table::from_frames(&cycles));
+        table.arguments = Option::Some(vec![path.as_ref().to_string_lossy().into_owned(),
+                                            frame_size.to_string()]);
+        Ok(table)
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// samples per frame.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // the level with the most harmonics that all stay below half the
+    // sample rate at `increment` periods per frame.
+    fn level(&self, increment: f64) -> usize {
+        let levels = self.frames[0].len();
+        let mut level = 0;
+        while level + 1 < levels && ((self.size / 2) >> level) as f64 * increment > 0.5 {
+            level += 1;
+        }
+        level
+    }
+
+    // `phase` in [0, 1), linearly interpolated.
+    fn read(&self, frame: usize, level: usize, phase: f64) -> f64 {
+        let samples = &self.frames[frame][level];
+        let position = phase * self.size as f64;
+        let index = (position as usize).min(self.size - 1);
+        let fraction = position - index as f64;
+        let next = samples[(index + 1) % self.size];
+        samples[index] + (next - samples[index]) * fraction
+    }
+}
+
+// the cycle resynthesized from its harmonics, with half of them left out
+// from one level to the next.
+fn mipmaps(cycle: &[f64], cosines: &[f64], sines: &[f64]) -> Vec<Vec<f64>> {
+    let size = cycle.len();
+    let mean = cycle.iter().sum::<f64>() / size as f64;
+    // (cosine, sine) amplitude of every harmonic below half the size.
+    let harmonics: Vec<(f64, f64)> = (1..size.div_ceil(2)).map(|k| {
+        let mut a = 0.0;
+        let mut b = 0.0;
+        for (n, sample) in cycle.iter().enumerate() {
+            a += sample * cosines[(k * n) % size];
+            b += sample * sines[(k * n) % size];
+        }
+        (2.0 * a / size as f64, 2.0 * b / size as f64)
+    }).collect();
+    let mut levels = Vec::new();
+    let mut limit = size / 2;
+    while limit >= 2 || levels.is_empty() {
+        let level = (0..size).map(|n| {
+            let mut sample = mean;
+            for (k, &(a, b)) in harmonics.iter().enumerate().take(limit - 1) {
+                let index = ((k + 1) * n) % size;
+                sample += a * cosines[index] + b * sines[index];
+            }
+            sample
+        }).collect();
+        levels.push(level);
+        limit /= 2;
+    }
+    levels
+}
+
+/// plays a `Table`. the position input morphs from the first frame at 0
+/// to the last at 1.
+pub struct Wavetable {
+    table: Arc<Table>,
+    phase: f64,
+    sample_rate: f64,
+}
+
+impl Wavetable {
+    pub fn new(table: Arc<Table>) -> Wavetable {
+        Wavetable { table: table, phase: 0.0, sample_rate: SAMPLERATE }
+    }
+
+    fn next(&mut self, freq: Signal, reset: Signal, position: Signal) -> f64 {
+        if reset == Signal::Trigger(true) {
+            self.phase = 0.0;
+        }
+        let increment = freq.value() / self.sample_rate;
+        let level = self.table.level(increment.abs());
+        let last = self.table.frames() - 1;
+        let position = position.value().clamp(0.0, 1.0) * last as f64;
+        let frame = (position as usize).min(last);
+        let fraction = position - frame as f64;
+        let mut output = self.table.read(frame, level, self.phase);
+        if fraction > 0.0 {
+            let next = self.table.read(frame + 1, level, self.phase);
+            output += (next - output) * fraction;
+        }
+        self.phase += increment;
+        self.phase -= self.phase.floor();
+        output
+    }
+}
+
+impl Processor for Wavetable {
+    fn process(&mut self, input: &Vec<Signal>) -> Vec<Signal> {
+        vec![Signal::Sound(self.next(input[0], input[1], input[2]))]
+    }
+
+    fn process_block(&mut self, input: &[Vec<Signal>], output: &mut [Vec<Signal>],
+                     frames: usize) {
+        for frame in 0..frames {
+            let value = self.next(input[0][frame], input[1][frame], input[2][frame]);
+            output[0][frame] = Signal::Sound(value);
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _block_size: usize) {
+        self.sample_rate = sample_rate;
+    }
+
+    fn input_types_and_defaults(&self) -> Vec<Signal> {
+        vec![Signal::Sound(440.0), Signal::Trigger(false), Signal::Sound(0.0)]
+    }
+
+    fn output_types(&self) -> Vec<Signal> {
+        vec![Signal::Sound(0.0)]
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        vec![Port::new("freq", "frequency").unit("Hz").range(0.0, self.sample_rate / 2.0),
+             Port::new("reset", "restarts the wave at the start of its period"),
+             Port::new("position", "morphs from the first frame to the last").range(0.0, 1.0)]
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("out", "the table")]
+    }
+
+    fn type_name(&self) -> String {
+        String::from("Wavetable")
+    }
+
+    fn arguments(&self) -> Option<Vec<String>> {
+        self.table.arguments.clone()
+    }
+}